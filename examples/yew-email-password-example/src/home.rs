@@ -1,10 +1,11 @@
-use crate::{Route, API_KEY};
-use firebase_auth_lite::{Auth, AuthOptions, UserProfile};
+use crate::Route;
+use firebase_auth_lite::{AuthError, AuthStateListener, UserProfile};
 use yew::prelude::*;
 use yew_router::components::Link;
 
 pub struct Home {
     user: Option<String>,
+    _listener: AuthStateListener,
 }
 
 pub enum HomeMsg {
@@ -19,19 +20,23 @@ impl Component for Home {
     type Properties = ();
 
     fn create(ctx: &Context<Self>) -> Self {
-        let auth = Auth::new(AuthOptions::new(API_KEY));
+        let auth = crate::auth(ctx);
 
-        ctx.link().send_future(async move {
-            if let Ok(prof) = auth.fetch_profile().await {
-                if let Ok(prof) = prof.into_serde() {
-                    let user_profile: UserProfile = prof;
-                    return HomeMsg::LoggedIn(user_profile.email);
-                }
-            }
-            HomeMsg::NotLoggedIn
-        });
+        let on_user =
+            ctx.link()
+                .callback(|user: Result<Option<UserProfile>, AuthError>| match user {
+                    Ok(Some(user_profile)) => {
+                        HomeMsg::LoggedIn(user_profile.email.unwrap_or(user_profile.local_id))
+                    }
+                    Ok(None) => HomeMsg::NotLoggedIn,
+                    Err(_) => HomeMsg::Nil,
+                });
+        let listener = auth.on_auth_state_changed(move |user| on_user.emit(user));
 
-        Home { user: None }
+        Home {
+            user: None,
+            _listener: listener,
+        }
     }
 
     fn update(&mut self, ctx: &Context<Self>, msg: Self::Message) -> bool {
@@ -46,9 +51,8 @@ impl Component for Home {
                 return false;
             }
             HomeMsg::Signout => {
+                let auth = crate::auth(ctx);
                 ctx.link().send_future(async move {
-                    let auth = Auth::new(AuthOptions::new(API_KEY));
-
                     if auth.sign_out().await.is_ok() {
                         HomeMsg::NotLoggedIn
                     } else {
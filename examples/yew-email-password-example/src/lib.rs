@@ -1,3 +1,7 @@
+// The `html!` macro of yew 0.19 expands to code that newer clippy lints flag.
+#![allow(clippy::let_unit_value, clippy::unnecessary_operation)]
+
+use firebase_auth_lite::{components::EmailActionHandler, Auth, AuthOptions};
 use yew::prelude::*;
 use yew_router::prelude::*;
 
@@ -15,6 +19,8 @@ pub enum Route {
     Login,
     #[at("/signup/")]
     Signup,
+    #[at("/auth/action")]
+    EmailAction,
 }
 
 fn switch(routes: &Route) -> Html {
@@ -22,14 +28,35 @@ fn switch(routes: &Route) -> Html {
         Route::Home => html! { <home::Home /> },
         Route::Login => html! { <login::Login />},
         Route::Signup => html! { <signup::Signup /> },
+        Route::EmailAction => html! { <EmailAction /> },
     }
 }
 
+#[function_component(EmailAction)]
+fn email_action() -> Html {
+    let auth = use_context::<Auth>().expect("the Auth is provided by App");
+    html! { <EmailActionHandler {auth} /> }
+}
+
+/// The `Auth` provided by `App`, for the struct components.
+pub fn auth<C: Component>(ctx: &Context<C>) -> Auth {
+    let (auth, _) = ctx
+        .link()
+        .context::<Auth>(Callback::noop())
+        .expect("the Auth is provided by App");
+    auth
+}
+
 #[function_component(App)]
 pub fn app() -> Html {
+    // Created once, not on every render.
+    let auth = use_state(|| Auth::new(AuthOptions::new(API_KEY)));
+
     html! {
-        <BrowserRouter>
-            <Switch<Route> render={Switch::render(switch)} />
-        </BrowserRouter>
+        <ContextProvider<Auth> context={(*auth).clone()}>
+            <BrowserRouter>
+                <Switch<Route> render={Switch::render(switch)} />
+            </BrowserRouter>
+        </ContextProvider<Auth>>
     }
 }
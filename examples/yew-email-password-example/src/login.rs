@@ -1,5 +1,5 @@
-use crate::{Route, API_KEY};
-use firebase_auth_lite::{Auth, AuthOptions};
+use crate::Route;
+use firebase_auth_lite::AuthError;
 use yew::prelude::*;
 use yew_router::{history::History, scope_ext::RouterScopeExt};
 
@@ -56,32 +56,23 @@ impl Component for Login {
                 false
             }
             LoginMsg::Login => {
-                let auth = Auth::new(AuthOptions::new(API_KEY));
+                let auth = crate::auth(ctx);
                 if let (Some(email), Some(password)) = (&self.email, &self.password) {
                     let email = email.clone();
                     let password = password.clone();
                     ctx.link().send_future(async move {
                         match auth.sign_in(email, password).await {
                             Ok(_) => LoginMsg::ChangeToHome,
-                            Err(obj) => {
-                                let e = js_sys::Error::from(obj);
-                                match e.message().as_string() {
-                                    Some(v) => {
-                                        if &v == "EMAIL_NOT_FOUND" {
-                                            LoginMsg::Error("User not found".into())
-                                        } else if &v == "INVALID_PASSWORD" {
-                                            LoginMsg::Error("Wrong Password".into())
-                                        } else if &v == "USER_DISABLED" {
-                                            LoginMsg::Error(
-                                                "User has been disabled by an administrator".into(),
-                                            )
-                                        } else {
-                                            LoginMsg::Error("There has been an error.".into())
-                                        }
-                                    }
-                                    None => LoginMsg::Error("There has been an error.".into()),
-                                }
+                            Err(AuthError::EmailNotFound) => {
+                                LoginMsg::Error("User not found".into())
                             }
+                            Err(AuthError::InvalidPassword) => {
+                                LoginMsg::Error("Wrong Password".into())
+                            }
+                            Err(AuthError::UserDisabled) => {
+                                LoginMsg::Error("User has been disabled by an administrator".into())
+                            }
+                            Err(_) => LoginMsg::Error("There has been an error.".into()),
                         }
                     });
 
@@ -1,5 +1,5 @@
-use crate::{Route, API_KEY};
-use firebase_auth_lite::{Auth, AuthOptions};
+use crate::Route;
+use firebase_auth_lite::AuthError;
 use yew::{events::InputEvent, prelude::*};
 use yew_router::{history::History, scope_ext::RouterScopeExt};
 
@@ -56,26 +56,17 @@ impl Component for Signup {
                 false
             }
             SignupMsg::Signup => {
-                let auth = Auth::new(AuthOptions::new(API_KEY));
+                let auth = crate::auth(ctx);
                 if let (Some(email), Some(password)) = (&self.email, &self.password) {
                     let email = email.clone();
                     let password = password.clone();
                     ctx.link().send_future(async move {
-                        match auth.signUp(email, password).await {
+                        match auth.sign_up(email, password).await {
                             Ok(_) => SignupMsg::ChangeToHome,
-                            Err(e) => {
-                                let v = js_sys::Error::from(e);
-                                match v.message().as_string() {
-                                    Some(v) => {
-                                        if &v == "EMAIL_EXISTS" {
-                                            SignupMsg::Error("Email Already Exists".into())
-                                        } else {
-                                            SignupMsg::Error("An Error Occured".into())
-                                        }
-                                    }
-                                    None => SignupMsg::Error("Something went wrong".into()),
-                                }
+                            Err(AuthError::EmailExists) => {
+                                SignupMsg::Error("Email Already Exists".into())
                             }
+                            Err(_) => SignupMsg::Error("An Error Occured".into()),
                         }
                     });
 
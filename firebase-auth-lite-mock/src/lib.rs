@@ -0,0 +1,162 @@
+//! An in-memory mock of the identitytoolkit and securetoken REST APIs,
+//! so `Auth` can be tested end-to-end without a network or a Firebase project.
+//!
+//! Point `AuthOptions::with_emulator` at `MockServer::host`:
+//!
+//! ```ignore
+//! let server = MockServer::start().await?;
+//! let auth = Auth::new(AuthOptions::new("any-api-key").with_emulator(&server.host()));
+//! ```
+//!
+//! Like the Auth Emulator, the API key isn't checked and emails aren't sent,
+//! the out-of-band codes can be read with `MockServer::oob_codes`.
+
+mod state;
+
+pub use state::{OobCode, PROJECT_ID};
+
+use axum::{
+    extract::{Path, State as Extract},
+    http::StatusCode,
+    response::{IntoResponse, Response},
+    routing::post,
+    Json, Router,
+};
+use serde::de::DeserializeOwned;
+use serde_json::{json, Value};
+use state::{ApiError, State};
+use std::{
+    io,
+    net::SocketAddr,
+    sync::{Arc, Mutex},
+    time::Duration,
+};
+use tokio::{
+    net::{TcpListener, ToSocketAddrs},
+    sync::oneshot,
+};
+use tower_http::cors::CorsLayer;
+
+type SharedState = Arc<Mutex<State>>;
+
+/// A running mock server, it shuts down when dropped.
+pub struct MockServer {
+    addr: SocketAddr,
+    state: SharedState,
+    _shutdown: oneshot::Sender<()>,
+}
+
+impl MockServer {
+    /// Starts a server on a free port of `127.0.0.1`.
+    pub async fn start() -> io::Result<Self> {
+        MockServer::bind("127.0.0.1:0").await
+    }
+
+    /// Starts a server on `addr`, it must be called from within a tokio runtime.
+    pub async fn bind<A: ToSocketAddrs>(addr: A) -> io::Result<Self> {
+        let listener = TcpListener::bind(addr).await?;
+        let addr = listener.local_addr()?;
+
+        let state = SharedState::default();
+        let (shutdown, on_shutdown) = oneshot::channel::<()>();
+
+        let app = router(state.clone());
+        tokio::spawn(async move {
+            let _ = axum::serve(listener, app)
+                .with_graceful_shutdown(async {
+                    let _ = on_shutdown.await;
+                })
+                .await;
+        });
+
+        Ok(MockServer {
+            addr,
+            state,
+            _shutdown: shutdown,
+        })
+    }
+
+    pub fn addr(&self) -> SocketAddr {
+        self.addr
+    }
+
+    /// The host to pass to `AuthOptions::with_emulator`, e.g. `127.0.0.1:9099`.
+    pub fn host(&self) -> String {
+        self.addr.to_string()
+    }
+
+    /// The out-of-band codes that would have been emailed, oldest first.
+    ///
+    /// Codes are removed once they are used.
+    pub fn oob_codes(&self) -> Vec<OobCode> {
+        self.state.lock().unwrap().oob_codes.clone()
+    }
+
+    /// How long the ID tokens issued from now on are valid for, an hour by default.
+    pub fn set_token_lifetime(&self, lifetime: Duration) {
+        self.state.lock().unwrap().token_lifetime = chrono::Duration::from_std(lifetime).ok();
+    }
+
+    /// Disables the account of `email`, so signing in and refreshing fail with `USER_DISABLED`.
+    pub fn disable_user(&self, email: &str) {
+        self.state.lock().unwrap().disable_user(email);
+    }
+
+    /// Invalidates the refresh tokens of `email`, so refreshing fails with `INVALID_REFRESH_TOKEN`.
+    pub fn revoke_refresh_tokens(&self, email: &str) {
+        self.state.lock().unwrap().revoke_refresh_tokens(email);
+    }
+
+    /// Sets the custom claims of `email`, returned as `customAttributes` by `accounts:lookup`
+    /// and added to the ID tokens issued from now on.
+    pub fn set_custom_claims(&self, email: &str, claims: &Value) {
+        self.state.lock().unwrap().set_custom_claims(email, claims);
+    }
+}
+
+fn router(state: SharedState) -> Router {
+    Router::new()
+        .route(
+            "/identitytoolkit.googleapis.com/v1/{endpoint}",
+            post(accounts),
+        )
+        .route("/securetoken.googleapis.com/v1/token", post(token))
+        // `main.js` calls the API from the page, so the browser tests need CORS.
+        .layer(CorsLayer::permissive())
+        .with_state(state)
+}
+
+async fn accounts(
+    Extract(state): Extract<SharedState>,
+    Path(endpoint): Path<String>,
+    body: String,
+) -> Result<Json<Value>, ApiError> {
+    let request = parse(&body)?;
+    state.lock().unwrap().accounts(&endpoint, request).map(Json)
+}
+
+async fn token(
+    Extract(state): Extract<SharedState>,
+    body: String,
+) -> Result<Json<Value>, ApiError> {
+    let request = parse(&body)?;
+    state.lock().unwrap().token(request).map(Json)
+}
+
+/// `main.js` doesn't set a `Content-Type`, so the body is always parsed as JSON.
+fn parse<T: DeserializeOwned>(body: &str) -> Result<T, ApiError> {
+    serde_json::from_str(body).map_err(|_| ApiError("INVALID_JSON_PAYLOAD"))
+}
+
+impl IntoResponse for ApiError {
+    fn into_response(self) -> Response {
+        let body = json!({
+            "error": {
+                "code": 400,
+                "message": self.0,
+                "errors": [{ "message": self.0, "domain": "global", "reason": "invalid" }],
+            }
+        });
+        (StatusCode::BAD_REQUEST, Json(body)).into_response()
+    }
+}
@@ -0,0 +1,19 @@
+//! Runs the mock server for manual testing, e.g. of the examples or the browser tests.
+//!
+//! `firebase-auth-lite-mock [ADDR]`, listening on `127.0.0.1:9099` by default,
+//! the port of the Firebase Auth Emulator.
+
+use firebase_auth_lite_mock::MockServer;
+
+#[tokio::main(flavor = "current_thread")]
+async fn main() -> std::io::Result<()> {
+    let addr = std::env::args()
+        .nth(1)
+        .unwrap_or_else(|| "127.0.0.1:9099".into());
+
+    let server = MockServer::bind(addr).await?;
+    println!("Listening on http://{}", server.host());
+
+    std::future::pending::<()>().await;
+    Ok(())
+}
@@ -0,0 +1,1028 @@
+use base64::{engine::general_purpose::URL_SAFE_NO_PAD, Engine};
+use chrono::{DateTime, Duration, Utc};
+use serde::Deserialize;
+use serde_json::{json, Value};
+use std::collections::HashMap;
+use url::Url;
+
+/// The `aud` of the ID tokens issued by the mock server.
+pub const PROJECT_ID: &str = "mock-project";
+
+/// An identitytoolkit error, sent as `{ "error": { "code": 400, "message": ... } }`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub(crate) struct ApiError(pub(crate) &'static str);
+
+type ApiResult = Result<Value, ApiError>;
+
+/// An out-of-band code "emailed" by `accounts:sendOobCode`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct OobCode {
+    pub code: String,
+    /// `PASSWORD_RESET`, `VERIFY_EMAIL`, `EMAIL_SIGNIN`, `VERIFY_AND_CHANGE_EMAIL`
+    /// or `RECOVER_EMAIL`.
+    pub request_type: String,
+    pub email: String,
+    /// The email a `VERIFY_AND_CHANGE_EMAIL` code changes to, or the
+    /// one a `RECOVER_EMAIL` code changes back from.
+    pub new_email: Option<String>,
+    pub continue_url: Option<String>,
+    pub can_handle_code_in_app: bool,
+    pub ios_bundle_id: Option<String>,
+    pub android_package_name: Option<String>,
+}
+
+impl OobCode {
+    /// The link of the email as it reaches an app that handles the code,
+    /// i.e. the continue URL with the `mode` and `oobCode` of the action.
+    pub fn link(&self) -> String {
+        let mode = match self.request_type.as_str() {
+            "PASSWORD_RESET" => "resetPassword",
+            "VERIFY_EMAIL" => "verifyEmail",
+            "EMAIL_SIGNIN" => "signIn",
+            "VERIFY_AND_CHANGE_EMAIL" => "verifyAndChangeEmail",
+            "RECOVER_EMAIL" => "recoverEmail",
+            other => other,
+        };
+
+        let continue_url = self.continue_url.as_deref().unwrap_or("http://localhost/");
+        let mut link = Url::parse(continue_url)
+            .unwrap_or_else(|_| Url::parse("http://localhost/").expect("a valid URL"));
+        link.query_pairs_mut()
+            .append_pair("apiKey", "any-api-key")
+            .append_pair("mode", mode)
+            .append_pair("oobCode", &self.code)
+            .append_pair("continueUrl", continue_url);
+        link.into()
+    }
+}
+
+#[derive(Default)]
+pub(crate) struct State {
+    users: HashMap<String, User>,
+    id_tokens: HashMap<String, Token>,
+    refresh_tokens: HashMap<String, Session>,
+    pub(crate) oob_codes: Vec<OobCode>,
+    auth_sessions: HashMap<String, AuthSession>,
+    pub(crate) token_lifetime: Option<Duration>,
+    next_id: u64,
+}
+
+#[derive(Clone)]
+struct User {
+    local_id: String,
+    email: Option<String>,
+    email_verified: bool,
+    password: Option<String>,
+    display_name: Option<String>,
+    photo_url: Option<String>,
+    disabled: bool,
+    custom_auth: bool,
+    custom_attributes: Option<String>,
+    providers: Vec<Provider>,
+    created_at: DateTime<Utc>,
+    last_login_at: Option<DateTime<Utc>>,
+    last_refresh_at: Option<DateTime<Utc>>,
+    password_updated_at: Option<DateTime<Utc>>,
+    valid_since: DateTime<Utc>,
+}
+
+#[derive(Clone)]
+struct Provider {
+    provider_id: String,
+    federated_id: String,
+    email: Option<String>,
+}
+
+struct Token {
+    local_id: String,
+    expires_at: DateTime<Utc>,
+}
+
+/// What a refresh token was issued for, the ID tokens it refreshes keep these claims.
+struct Session {
+    local_id: String,
+    sign_in_provider: String,
+    auth_time: DateTime<Utc>,
+}
+
+/// A pending `createAuthUri` flow, keyed by the `code` the mock IdP redirects back with.
+struct AuthSession {
+    session_id: String,
+    provider_id: String,
+    context: Option<String>,
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub(crate) struct Request {
+    id_token: Option<String>,
+    email: Option<String>,
+    password: Option<String>,
+    new_password: Option<String>,
+    display_name: Option<String>,
+    photo_url: Option<String>,
+    #[serde(default)]
+    delete_attribute: Vec<String>,
+    request_type: Option<String>,
+    new_email: Option<String>,
+    continue_url: Option<String>,
+    #[serde(default)]
+    can_handle_code_in_app: bool,
+    #[serde(rename = "iOSBundleId")]
+    ios_bundle_id: Option<String>,
+    android_package_name: Option<String>,
+    continue_uri: Option<String>,
+    identifier: Option<String>,
+    provider_id: Option<String>,
+    context: Option<String>,
+    request_uri: Option<String>,
+    session_id: Option<String>,
+    oob_code: Option<String>,
+    token: Option<String>,
+    #[serde(default)]
+    return_secure_token: bool,
+}
+
+#[derive(Deserialize)]
+pub(crate) struct TokenRequest {
+    grant_type: Option<String>,
+    refresh_token: Option<String>,
+}
+
+impl State {
+    /// Handles an `accounts:<endpoint>` request.
+    pub(crate) fn accounts(&mut self, endpoint: &str, request: Request) -> ApiResult {
+        match endpoint {
+            "accounts:signUp" => self.sign_up(request),
+            "accounts:signInWithPassword" => self.sign_in_with_password(request),
+            "accounts:signInWithCustomToken" => self.sign_in_with_custom_token(request),
+            "accounts:signInWithEmailLink" => self.sign_in_with_email_link(request),
+            "accounts:createAuthUri" => self.create_auth_uri(request),
+            "accounts:signInWithIdp" => self.sign_in_with_idp(request),
+            "accounts:lookup" => self.lookup(request),
+            "accounts:update" => self.update(request),
+            "accounts:delete" => self.delete(request),
+            "accounts:sendOobCode" => self.send_oob_code(request),
+            "accounts:resetPassword" => self.reset_password(request),
+            _ => Err(ApiError("NOT_FOUND")),
+        }
+    }
+
+    /// Handles a securetoken `token` request.
+    pub(crate) fn token(&mut self, request: TokenRequest) -> ApiResult {
+        if request.grant_type.as_deref() != Some("refresh_token") {
+            return Err(ApiError("INVALID_GRANT_TYPE"));
+        }
+        let refresh_token = request
+            .refresh_token
+            .ok_or(ApiError("MISSING_REFRESH_TOKEN"))?;
+        let local_id = self
+            .refresh_tokens
+            .get(&refresh_token)
+            .map(|session| session.local_id.clone())
+            .ok_or(ApiError("INVALID_REFRESH_TOKEN"))?;
+
+        let user = self
+            .users
+            .get_mut(&local_id)
+            .ok_or(ApiError("USER_NOT_FOUND"))?;
+        if user.disabled {
+            return Err(ApiError("USER_DISABLED"));
+        }
+        user.last_refresh_at = Some(Utc::now());
+
+        let (id_token, expires_in) = self.issue_id_token(&refresh_token);
+        Ok(json!({
+            "access_token": id_token,
+            "expires_in": expires_in,
+            "token_type": "Bearer",
+            "refresh_token": refresh_token,
+            "id_token": id_token,
+            "user_id": local_id,
+        }))
+    }
+
+    /// Disables the account of `email`, so signing in and refreshing fail with `USER_DISABLED`.
+    pub(crate) fn disable_user(&mut self, email: &str) {
+        if let Some(user) = self.user_by_email_mut(email) {
+            user.disabled = true;
+        }
+    }
+
+    /// Invalidates every refresh token of `email`, like revoking the sessions with the Admin SDK.
+    pub(crate) fn revoke_refresh_tokens(&mut self, email: &str) {
+        let Some(local_id) = self.user_by_email(email).map(|user| user.local_id.clone()) else {
+            return;
+        };
+        self.refresh_tokens
+            .retain(|_, session| session.local_id != local_id);
+    }
+
+    /// Sets the custom claims of `email`, like `setCustomUserClaims` of the Admin SDK.
+    pub(crate) fn set_custom_claims(&mut self, email: &str, claims: &Value) {
+        if let Some(user) = self.user_by_email_mut(email) {
+            user.custom_attributes = Some(claims.to_string());
+        }
+    }
+
+    fn sign_up(&mut self, request: Request) -> ApiResult {
+        let user = match (request.email, request.password) {
+            // Without credentials the account is anonymous.
+            (None, None) => self.new_user(None),
+            (Some(_), None) => return Err(ApiError("MISSING_PASSWORD")),
+            (None, Some(_)) => return Err(ApiError("MISSING_EMAIL")),
+            (Some(email), Some(password)) => {
+                validate_email(&email)?;
+                validate_password(&password)?;
+                if self.user_by_email(&email).is_some() {
+                    return Err(ApiError("EMAIL_EXISTS"));
+                }
+
+                let mut user = self.new_user(Some(email.clone()));
+                user.password = Some(password);
+                user.password_updated_at = Some(Utc::now());
+                user.providers.push(Provider::password(&email));
+                user
+            }
+        };
+
+        let local_id = user.local_id.clone();
+        let sign_in_provider = if user.email.is_some() {
+            "password"
+        } else {
+            "anonymous"
+        };
+        self.users.insert(local_id.clone(), user);
+        self.sign_in_response(
+            &local_id,
+            sign_in_provider,
+            json!({ "kind": "identitytoolkit#SignupNewUserResponse" }),
+        )
+    }
+
+    fn sign_in_with_password(&mut self, request: Request) -> ApiResult {
+        let email = request.email.ok_or(ApiError("MISSING_EMAIL"))?;
+        let password = request.password.ok_or(ApiError("MISSING_PASSWORD"))?;
+
+        let user = self
+            .user_by_email(&email)
+            .ok_or(ApiError("EMAIL_NOT_FOUND"))?;
+        if user.password.as_deref() != Some(password.as_str()) {
+            return Err(ApiError("INVALID_PASSWORD"));
+        }
+        if user.disabled {
+            return Err(ApiError("USER_DISABLED"));
+        }
+
+        let local_id = user.local_id.clone();
+        self.sign_in_response(
+            &local_id,
+            "password",
+            json!({ "kind": "identitytoolkit#VerifyPasswordResponse", "registered": true }),
+        )
+    }
+
+    /// The mock doesn't check signatures, the custom token is used as the uid of the user.
+    fn sign_in_with_custom_token(&mut self, request: Request) -> ApiResult {
+        let uid = request
+            .token
+            .filter(|token| !token.is_empty() && !token.contains(char::is_whitespace))
+            .ok_or(ApiError("INVALID_CUSTOM_TOKEN"))?;
+
+        let is_new_user = !self.users.contains_key(&uid);
+        if is_new_user {
+            let mut user = self.new_user(None);
+            user.local_id = uid.clone();
+            user.custom_auth = true;
+            self.users.insert(uid.clone(), user);
+        } else if self.users[&uid].disabled {
+            return Err(ApiError("USER_DISABLED"));
+        }
+
+        self.sign_in_response(
+            &uid,
+            "custom",
+            json!({
+                "kind": "identitytoolkit#VerifyCustomTokenResponse",
+                "isNewUser": is_new_user,
+            }),
+        )
+    }
+
+    fn sign_in_with_email_link(&mut self, request: Request) -> ApiResult {
+        let email = request.email.ok_or(ApiError("MISSING_EMAIL"))?;
+        let oob_code = request.oob_code.ok_or(ApiError("MISSING_OOB_CODE"))?;
+
+        let index = self
+            .oob_codes
+            .iter()
+            .position(|code| code.code == oob_code && code.request_type == "EMAIL_SIGNIN")
+            .ok_or(ApiError("INVALID_OOB_CODE"))?;
+        if self.oob_codes[index].email != email {
+            return Err(ApiError("INVALID_EMAIL"));
+        }
+        self.oob_codes.remove(index);
+
+        let is_new_user = self.user_by_email(&email).is_none();
+        if is_new_user {
+            let mut user = self.new_user(Some(email.clone()));
+            user.providers.push(Provider::password(&email));
+            self.users.insert(user.local_id.clone(), user);
+        }
+
+        let user = self
+            .user_by_email_mut(&email)
+            .ok_or(ApiError("EMAIL_NOT_FOUND"))?;
+        if user.disabled {
+            return Err(ApiError("USER_DISABLED"));
+        }
+        user.email_verified = true;
+
+        let local_id = user.local_id.clone();
+        self.sign_in_response(
+            &local_id,
+            "password",
+            json!({ "kind": "identitytoolkit#EmailLinkSigninResponse", "isNewUser": is_new_user }),
+        )
+    }
+
+    /// With a `providerId`, starts an OAuth flow whose `authUri` skips the IdP and
+    /// redirects straight back to `continueUri` with a `code`.
+    /// Otherwise, returns the sign-in methods of the `identifier` email.
+    fn create_auth_uri(&mut self, request: Request) -> ApiResult {
+        let continue_uri = request
+            .continue_uri
+            .ok_or(ApiError("MISSING_CONTINUE_URI"))?;
+        let session_id = self.next_token("mock-session");
+
+        if let Some(provider_id) = request.provider_id {
+            let code = self.next_token("mock-auth-code");
+            let separator = if continue_uri.contains('?') { '&' } else { '?' };
+            let auth_uri = format!(
+                "{}{}code={}&state={}",
+                continue_uri, separator, code, session_id
+            );
+
+            self.auth_sessions.insert(
+                code,
+                AuthSession {
+                    session_id: session_id.clone(),
+                    provider_id: provider_id.clone(),
+                    context: request.context,
+                },
+            );
+
+            return Ok(json!({
+                "kind": "identitytoolkit#CreateAuthUriResponse",
+                "authUri": auth_uri,
+                "providerId": provider_id,
+                "sessionId": session_id,
+            }));
+        }
+
+        let email = request.identifier.ok_or(ApiError("MISSING_IDENTIFIER"))?;
+        validate_email(&email)?;
+
+        let (all_providers, signin_methods) = match self.user_by_email(&email) {
+            Some(user) => {
+                let providers: Vec<_> = user
+                    .providers
+                    .iter()
+                    .map(|provider| provider.provider_id.clone())
+                    .collect();
+                let methods: Vec<_> = user
+                    .providers
+                    .iter()
+                    .map(|provider| match provider.provider_id.as_str() {
+                        "password" if user.password.is_none() => "emailLink".to_string(),
+                        provider_id => provider_id.to_string(),
+                    })
+                    .collect();
+                (providers, methods)
+            }
+            None => (Vec::new(), Vec::new()),
+        };
+
+        Ok(json!({
+            "kind": "identitytoolkit#CreateAuthUriResponse",
+            "allProviders": all_providers,
+            "registered": !signin_methods.is_empty(),
+            "sessionId": session_id,
+            "signinMethods": signin_methods,
+        }))
+    }
+
+    /// Finishes a `createAuthUri` flow, the IdP account is `user@<providerId>`.
+    fn sign_in_with_idp(&mut self, request: Request) -> ApiResult {
+        let request_uri = request.request_uri.ok_or(ApiError("MISSING_REQUEST_URI"))?;
+        let code = query_param(&request_uri, "code").ok_or(ApiError("INVALID_IDP_RESPONSE"))?;
+
+        let session = self
+            .auth_sessions
+            .remove(&code)
+            .ok_or(ApiError("INVALID_IDP_RESPONSE"))?;
+        if request.session_id.as_deref() != Some(session.session_id.as_str()) {
+            return Err(ApiError("MISSING_OR_INVALID_NONCE"));
+        }
+
+        let email = format!("user@{}", session.provider_id);
+        let federated_id = format!("https://{}/{}", session.provider_id, email);
+        let provider = Provider {
+            provider_id: session.provider_id.clone(),
+            federated_id: federated_id.clone(),
+            email: Some(email.clone()),
+        };
+
+        let linked = self
+            .users
+            .values()
+            .find(|user| {
+                user.providers
+                    .iter()
+                    .any(|provider| provider.federated_id == federated_id)
+            })
+            .map(|user| user.local_id.clone());
+
+        let (local_id, is_new_user) = match (request.id_token, linked) {
+            // Linking adds the provider to the signed-in account.
+            (Some(id_token), _) => {
+                let local_id = self.user_for(&id_token)?.local_id.clone();
+                let user = self
+                    .users
+                    .get_mut(&local_id)
+                    .ok_or(ApiError("USER_NOT_FOUND"))?;
+                user.providers.push(provider);
+                user.email.get_or_insert(email.clone());
+                (local_id, false)
+            }
+            (None, Some(local_id)) => (local_id, false),
+            (None, None) => {
+                let mut user = self.new_user(Some(email.clone()));
+                user.email_verified = true;
+                user.providers.push(provider);
+                let local_id = user.local_id.clone();
+                self.users.insert(local_id.clone(), user);
+                (local_id, true)
+            }
+        };
+
+        if self.users[&local_id].disabled {
+            return Err(ApiError("USER_DISABLED"));
+        }
+
+        self.sign_in_response(
+            &local_id,
+            &session.provider_id,
+            json!({
+                "kind": "identitytoolkit#VerifyAssertionResponse",
+                "providerId": session.provider_id,
+                "federatedId": federated_id,
+                "isNewUser": is_new_user,
+                "context": session.context,
+            }),
+        )
+    }
+
+    fn lookup(&mut self, request: Request) -> ApiResult {
+        let id_token = request.id_token.ok_or(ApiError("MISSING_ID_TOKEN"))?;
+        let user = self.user_for(&id_token)?;
+
+        Ok(json!({
+            "kind": "identitytoolkit#GetAccountInfoResponse",
+            "users": [user.to_json()],
+        }))
+    }
+
+    /// Applies an `oobCode`, or updates the account of the `idToken`.
+    fn update(&mut self, request: Request) -> ApiResult {
+        if let Some(oob_code) = request.oob_code {
+            return self.apply_oob_code(&oob_code);
+        }
+
+        let id_token = request.id_token.ok_or(ApiError("MISSING_ID_TOKEN"))?;
+        let local_id = self.user_for(&id_token)?.local_id.clone();
+
+        if let Some(email) = &request.email {
+            validate_email(email)?;
+            if self
+                .user_by_email(email)
+                .is_some_and(|user| user.local_id != local_id)
+            {
+                return Err(ApiError("EMAIL_EXISTS"));
+            }
+        }
+        if let Some(password) = &request.password {
+            validate_password(password)?;
+        }
+
+        let user = self
+            .users
+            .get_mut(&local_id)
+            .ok_or(ApiError("USER_NOT_FOUND"))?;
+        let credentials_changed = request.email.is_some() || request.password.is_some();
+        let mut changed_email = None;
+
+        if let Some(email) = request.email {
+            if let Some(previous) = user.email.clone().filter(|previous| *previous != email) {
+                changed_email = Some((previous, email.clone()));
+            }
+            user.email_verified = false;
+            user.set_email(email);
+        }
+        if let Some(password) = request.password {
+            user.password = Some(password);
+            user.password_updated_at = Some(Utc::now());
+        }
+        if user.password.is_some() && !user.has_provider("password") {
+            let email = user.email.clone().unwrap_or_default();
+            user.providers.push(Provider::password(&email));
+        }
+        if let Some(display_name) = request.display_name {
+            user.display_name = Some(display_name);
+        }
+        if let Some(photo_url) = request.photo_url {
+            user.photo_url = Some(photo_url);
+        }
+        for attribute in &request.delete_attribute {
+            match attribute.as_str() {
+                "DISPLAY_NAME" => user.display_name = None,
+                "PHOTO_URL" => user.photo_url = None,
+                _ => return Err(ApiError("INVALID_DELETE_ATTRIBUTE")),
+            }
+        }
+
+        let mut response = user.to_json();
+        response["kind"] = "identitytoolkit#SetAccountInfoResponse".into();
+
+        if let Some((previous, email)) = changed_email {
+            self.send_recover_email(previous, email);
+        }
+
+        // Changing the credentials issues a new ID token, like the real API.
+        if credentials_changed && request.return_secure_token {
+            let refresh_token = self.issue_refresh_token(&local_id, "password");
+            let (id_token, expires_in) = self.issue_id_token(&refresh_token);
+            response["idToken"] = id_token.into();
+            response["refreshToken"] = refresh_token.into();
+            response["expiresIn"] = expires_in.into();
+        }
+
+        Ok(response)
+    }
+
+    fn delete(&mut self, request: Request) -> ApiResult {
+        let id_token = request.id_token.ok_or(ApiError("MISSING_ID_TOKEN"))?;
+        let local_id = self.user_for(&id_token)?.local_id.clone();
+
+        self.users.remove(&local_id);
+        self.id_tokens.retain(|_, token| token.local_id != local_id);
+        self.refresh_tokens
+            .retain(|_, session| session.local_id != local_id);
+
+        Ok(json!({ "kind": "identitytoolkit#DeleteAccountResponse" }))
+    }
+
+    fn send_oob_code(&mut self, request: Request) -> ApiResult {
+        let request_type = request.request_type.ok_or(ApiError("MISSING_REQ_TYPE"))?;
+        let mut new_email = None;
+
+        let email = match request_type.as_str() {
+            "VERIFY_EMAIL" => {
+                let id_token = request.id_token.ok_or(ApiError("MISSING_ID_TOKEN"))?;
+                self.user_for(&id_token)?
+                    .email
+                    .clone()
+                    .ok_or(ApiError("MISSING_EMAIL"))?
+            }
+            "PASSWORD_RESET" => {
+                let email = request.email.ok_or(ApiError("MISSING_EMAIL"))?;
+                self.user_by_email(&email)
+                    .ok_or(ApiError("EMAIL_NOT_FOUND"))?;
+                email
+            }
+            "EMAIL_SIGNIN" => {
+                let email = request.email.ok_or(ApiError("MISSING_EMAIL"))?;
+                validate_email(&email)?;
+                email
+            }
+            "VERIFY_AND_CHANGE_EMAIL" => {
+                let id_token = request.id_token.ok_or(ApiError("MISSING_ID_TOKEN"))?;
+                let requested = request.new_email.ok_or(ApiError("MISSING_NEW_EMAIL"))?;
+                validate_email(&requested)?;
+                if self.user_by_email(&requested).is_some() {
+                    return Err(ApiError("EMAIL_EXISTS"));
+                }
+                new_email = Some(requested);
+                self.user_for(&id_token)?
+                    .email
+                    .clone()
+                    .ok_or(ApiError("MISSING_EMAIL"))?
+            }
+            _ => return Err(ApiError("INVALID_REQ_TYPE")),
+        };
+
+        let code = self.next_token("mock-oob-code");
+        self.oob_codes.push(OobCode {
+            code,
+            request_type,
+            email: email.clone(),
+            new_email,
+            continue_url: request.continue_url,
+            can_handle_code_in_app: request.can_handle_code_in_app,
+            ios_bundle_id: request.ios_bundle_id,
+            android_package_name: request.android_package_name,
+        });
+
+        Ok(json!({ "kind": "identitytoolkit#GetOobConfirmationCodeResponse", "email": email }))
+    }
+
+    /// Resets the password with a `PASSWORD_RESET` `oobCode`.
+    /// Without a `newPassword`, only returns what the code is for.
+    fn reset_password(&mut self, request: Request) -> ApiResult {
+        let oob_code = request.oob_code.ok_or(ApiError("MISSING_OOB_CODE"))?;
+
+        let Some(new_password) = request.new_password else {
+            let code = self
+                .oob_codes
+                .iter()
+                .find(|code| code.code == oob_code)
+                .ok_or(ApiError("INVALID_OOB_CODE"))?;
+
+            let mut response = json!({
+                "kind": "identitytoolkit#ResetPasswordResponse",
+                "email": code.email,
+                "requestType": code.request_type,
+            });
+            if let Some(new_email) = &code.new_email {
+                response["newEmail"] = new_email.clone().into();
+            }
+            return Ok(response);
+        };
+
+        validate_password(&new_password)?;
+        let code = self.take_oob_code(&oob_code, "PASSWORD_RESET")?;
+        let user = self
+            .user_by_email_mut(&code.email)
+            .ok_or(ApiError("EMAIL_NOT_FOUND"))?;
+        if user.disabled {
+            return Err(ApiError("USER_DISABLED"));
+        }
+        user.password = Some(new_password);
+        user.password_updated_at = Some(Utc::now());
+
+        Ok(json!({
+            "kind": "identitytoolkit#ResetPasswordResponse",
+            "email": code.email,
+            "requestType": "PASSWORD_RESET",
+        }))
+    }
+
+    /// Issues new tokens for `local_id` and adds them to `response`.
+    fn sign_in_response(
+        &mut self,
+        local_id: &str,
+        sign_in_provider: &str,
+        mut response: Value,
+    ) -> ApiResult {
+        let user = self
+            .users
+            .get_mut(local_id)
+            .ok_or(ApiError("USER_NOT_FOUND"))?;
+        user.last_login_at = Some(Utc::now());
+        let email = user.email.clone();
+
+        let refresh_token = self.issue_refresh_token(local_id, sign_in_provider);
+        let (id_token, expires_in) = self.issue_id_token(&refresh_token);
+
+        response["localId"] = local_id.into();
+        response["email"] = email.unwrap_or_default().into();
+        response["idToken"] = id_token.into();
+        response["refreshToken"] = refresh_token.into();
+        response["expiresIn"] = expires_in.into();
+        Ok(response)
+    }
+
+    /// Returns a new ID token for the session of `refresh_token` and its `expiresIn`, in seconds.
+    ///
+    /// The ID token is an unsigned JWT with the claims of a real one, like the Auth Emulator issues.
+    fn issue_id_token(&mut self, refresh_token: &str) -> (String, String) {
+        let lifetime = self.token_lifetime.unwrap_or(Duration::hours(1));
+        let session = &self.refresh_tokens[refresh_token];
+        let user = &self.users[&session.local_id];
+        let now = Utc::now();
+
+        let mut claims = json!({
+            "iss": format!("https://securetoken.google.com/{}", PROJECT_ID),
+            "aud": PROJECT_ID,
+            "auth_time": session.auth_time.timestamp(),
+            "user_id": user.local_id,
+            "sub": user.local_id,
+            "iat": now.timestamp(),
+            "exp": (now + lifetime).timestamp(),
+            "email_verified": user.email_verified,
+            "firebase": {
+                "identities": user.identities(),
+                "sign_in_provider": session.sign_in_provider,
+            },
+        });
+        let optional = [
+            ("email", &user.email),
+            ("name", &user.display_name),
+            ("picture", &user.photo_url),
+        ];
+        for (key, value) in optional {
+            if let Some(value) = value {
+                claims[key] = value.as_str().into();
+            }
+        }
+        let custom_claims = user
+            .custom_attributes
+            .as_deref()
+            .and_then(|claims| serde_json::from_str::<serde_json::Map<_, _>>(claims).ok());
+        for (key, value) in custom_claims.into_iter().flatten() {
+            claims[key] = value;
+        }
+
+        let id_token = format!(
+            "{}.{}.",
+            URL_SAFE_NO_PAD.encode(r#"{"alg":"none","typ":"JWT"}"#),
+            URL_SAFE_NO_PAD.encode(claims.to_string())
+        );
+        self.id_tokens.insert(
+            id_token.clone(),
+            Token {
+                local_id: user.local_id.clone(),
+                expires_at: now + lifetime,
+            },
+        );
+        (id_token, lifetime.num_seconds().to_string())
+    }
+
+    fn issue_refresh_token(&mut self, local_id: &str, sign_in_provider: &str) -> String {
+        let refresh_token = self.next_token("mock-refresh-token");
+        self.refresh_tokens.insert(
+            refresh_token.clone(),
+            Session {
+                local_id: local_id.into(),
+                sign_in_provider: sign_in_provider.into(),
+                auth_time: Utc::now(),
+            },
+        );
+        refresh_token
+    }
+
+    /// The user an unexpired ID token was issued for.
+    fn user_for(&self, id_token: &str) -> Result<&User, ApiError> {
+        let token = self
+            .id_tokens
+            .get(id_token)
+            .ok_or(ApiError("INVALID_ID_TOKEN"))?;
+        if Utc::now() >= token.expires_at {
+            return Err(ApiError("TOKEN_EXPIRED"));
+        }
+
+        let user = self
+            .users
+            .get(&token.local_id)
+            .ok_or(ApiError("USER_NOT_FOUND"))?;
+        if user.disabled {
+            return Err(ApiError("USER_DISABLED"));
+        }
+        Ok(user)
+    }
+
+    fn user_by_email(&self, email: &str) -> Option<&User> {
+        self.users
+            .values()
+            .find(|user| user.email.as_deref() == Some(email))
+    }
+
+    fn user_by_email_mut(&mut self, email: &str) -> Option<&mut User> {
+        self.users
+            .values_mut()
+            .find(|user| user.email.as_deref() == Some(email))
+    }
+
+    /// Applies a `VERIFY_EMAIL`, `VERIFY_AND_CHANGE_EMAIL` or `RECOVER_EMAIL` `oobCode`.
+    fn apply_oob_code(&mut self, oob_code: &str) -> ApiResult {
+        let index = self
+            .oob_codes
+            .iter()
+            .position(|code| {
+                code.code == oob_code
+                    && matches!(
+                        code.request_type.as_str(),
+                        "VERIFY_EMAIL" | "VERIFY_AND_CHANGE_EMAIL" | "RECOVER_EMAIL"
+                    )
+            })
+            .ok_or(ApiError("INVALID_OOB_CODE"))?;
+        let code = self.oob_codes.remove(index);
+
+        // A `RECOVER_EMAIL` code is sent to the previous email, and changes the email back.
+        let (current, new_email) = match code.request_type.as_str() {
+            "RECOVER_EMAIL" => (
+                code.new_email.clone().unwrap_or_default(),
+                Some(code.email.clone()),
+            ),
+            _ => (code.email.clone(), code.new_email.clone()),
+        };
+        let user = self
+            .user_by_email_mut(&current)
+            .ok_or(ApiError("EMAIL_NOT_FOUND"))?;
+        user.email_verified = true;
+        if let Some(new_email) = new_email.clone() {
+            user.set_email(new_email);
+        }
+        let response = json!({
+            "kind": "identitytoolkit#SetAccountInfoResponse",
+            "localId": user.local_id,
+            "email": user.email,
+            "emailVerified": true,
+        });
+
+        if code.request_type == "VERIFY_AND_CHANGE_EMAIL" {
+            if let Some(new_email) = new_email {
+                self.send_recover_email(current, new_email);
+            }
+        }
+        Ok(response)
+    }
+
+    /// "Emails" a `RECOVER_EMAIL` code to the `previous` email, like the real API
+    /// does whenever the email of an account changes.
+    fn send_recover_email(&mut self, previous: String, email: String) {
+        let code = self.next_token("mock-oob-code");
+        self.oob_codes.push(OobCode {
+            code,
+            request_type: "RECOVER_EMAIL".into(),
+            email: previous,
+            new_email: Some(email),
+            continue_url: None,
+            can_handle_code_in_app: false,
+            ios_bundle_id: None,
+            android_package_name: None,
+        });
+    }
+
+    fn take_oob_code(&mut self, oob_code: &str, request_type: &str) -> Result<OobCode, ApiError> {
+        let index = self
+            .oob_codes
+            .iter()
+            .position(|code| code.code == oob_code && code.request_type == request_type)
+            .ok_or(ApiError("INVALID_OOB_CODE"))?;
+        Ok(self.oob_codes.remove(index))
+    }
+
+    fn new_user(&mut self, email: Option<String>) -> User {
+        let now = Utc::now();
+        User {
+            local_id: self.next_token("mock-user"),
+            email,
+            email_verified: false,
+            password: None,
+            display_name: None,
+            photo_url: None,
+            disabled: false,
+            custom_auth: false,
+            custom_attributes: None,
+            providers: Vec::new(),
+            created_at: now,
+            last_login_at: None,
+            last_refresh_at: None,
+            password_updated_at: None,
+            valid_since: now,
+        }
+    }
+
+    fn next_token(&mut self, prefix: &str) -> String {
+        self.next_id += 1;
+        format!("{}-{}", prefix, self.next_id)
+    }
+}
+
+impl User {
+    fn set_email(&mut self, email: String) {
+        for provider in &mut self.providers {
+            if provider.provider_id == "password" {
+                *provider = Provider::password(&email);
+            }
+        }
+        self.email = Some(email);
+    }
+
+    fn has_provider(&self, provider_id: &str) -> bool {
+        self.providers
+            .iter()
+            .any(|provider| provider.provider_id == provider_id)
+    }
+
+    /// The `firebase.identities` claim, the identifiers of the user at each provider.
+    fn identities(&self) -> Value {
+        let mut identities = serde_json::Map::new();
+        for provider in &self.providers {
+            let (key, id) = match provider.provider_id.as_str() {
+                "password" => ("email", provider.email.clone().unwrap_or_default()),
+                provider_id => (provider_id, provider.federated_id.clone()),
+            };
+            identities.insert(key.into(), json!([id]));
+        }
+        identities.into()
+    }
+
+    /// The user as returned by `accounts:lookup`.
+    fn to_json(&self) -> Value {
+        let provider_user_info: Vec<_> = self
+            .providers
+            .iter()
+            .map(|provider| {
+                json!({
+                    "providerId": provider.provider_id,
+                    "federatedId": provider.federated_id,
+                    "rawId": provider.federated_id,
+                    "email": provider.email,
+                })
+            })
+            .collect();
+
+        let mut user = json!({
+            "localId": self.local_id,
+            "emailVerified": self.email_verified,
+            "disabled": self.disabled,
+            "customAuth": self.custom_auth,
+            "providerUserInfo": provider_user_info,
+            "validSince": self.valid_since.timestamp().to_string(),
+            "createdAt": self.created_at.timestamp_millis().to_string(),
+        });
+
+        let optional = [
+            ("email", self.email.clone().map(Value::from)),
+            ("displayName", self.display_name.clone().map(Value::from)),
+            ("photoUrl", self.photo_url.clone().map(Value::from)),
+            (
+                "customAttributes",
+                self.custom_attributes.clone().map(Value::from),
+            ),
+            (
+                "passwordHash",
+                self.password.as_ref().map(|_| "UkVEQUNURUQ=".into()),
+            ),
+            (
+                "passwordUpdatedAt",
+                self.password_updated_at
+                    .map(|at| at.timestamp_millis().into()),
+            ),
+            (
+                "lastLoginAt",
+                self.last_login_at
+                    .map(|at| at.timestamp_millis().to_string().into()),
+            ),
+            (
+                "lastRefreshAt",
+                self.last_refresh_at.map(|at| at.to_rfc3339().into()),
+            ),
+        ];
+        for (key, value) in optional {
+            if let Some(value) = value {
+                user[key] = value;
+            }
+        }
+
+        user
+    }
+}
+
+impl Provider {
+    fn password(email: &str) -> Self {
+        Provider {
+            provider_id: "password".into(),
+            federated_id: email.into(),
+            email: Some(email.into()),
+        }
+    }
+}
+
+fn validate_email(email: &str) -> Result<(), ApiError> {
+    match email.split_once('@') {
+        Some((name, domain)) if !name.is_empty() && !domain.is_empty() => Ok(()),
+        _ => Err(ApiError("INVALID_EMAIL")),
+    }
+}
+
+fn validate_password(password: &str) -> Result<(), ApiError> {
+    if password.len() < 6 {
+        return Err(ApiError(
+            "WEAK_PASSWORD : Password should be at least 6 characters",
+        ));
+    }
+    Ok(())
+}
+
+/// Finds a query parameter of `uri`, without decoding it.
+fn query_param(uri: &str, name: &str) -> Option<String> {
+    let (_, query) = uri.split_once('?')?;
+    query
+        .split('&')
+        .filter_map(|pair| pair.split_once('='))
+        .find(|(key, _)| *key == name)
+        .map(|(_, value)| value.to_string())
+}
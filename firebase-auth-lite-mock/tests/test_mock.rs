@@ -0,0 +1,246 @@
+use firebase_auth_lite_mock::MockServer;
+use serde_json::{json, Value};
+
+/// Calls an `accounts:<endpoint>` endpoint and returns the status and the body.
+async fn api(server: &MockServer, endpoint: &str, body: Value) -> (u16, Value) {
+    let response = reqwest::Client::new()
+        .post(format!(
+            "http://{}/identitytoolkit.googleapis.com/v1/accounts:{}?key=any-api-key",
+            server.host(),
+            endpoint
+        ))
+        .body(body.to_string())
+        .send()
+        .await
+        .unwrap();
+
+    (response.status().as_u16(), response.json().await.unwrap())
+}
+
+#[tokio::test]
+async fn test_mock_error_format() {
+    let server = MockServer::start().await.unwrap();
+
+    let (status, body) = api(
+        &server,
+        "signInWithPassword",
+        json!({ "email": "user@example.com", "password": "password" }),
+    )
+    .await;
+
+    assert_eq!(status, 400);
+    assert_eq!(body["error"]["message"], "EMAIL_NOT_FOUND");
+}
+
+#[tokio::test]
+async fn test_mock_anonymous_sign_up() {
+    let server = MockServer::start().await.unwrap();
+
+    let (status, body) = api(&server, "signUp", json!({ "returnSecureToken": true })).await;
+    assert_eq!(status, 200);
+
+    let (_, lookup) = api(&server, "lookup", json!({ "idToken": body["idToken"] })).await;
+    assert_eq!(lookup["users"][0]["localId"], body["localId"]);
+    assert_eq!(lookup["users"][0]["providerUserInfo"], json!([]));
+}
+
+#[tokio::test]
+async fn test_mock_create_auth_uri_for_email() {
+    let server = MockServer::start().await.unwrap();
+    api(
+        &server,
+        "signUp",
+        json!({ "email": "user@example.com", "password": "password" }),
+    )
+    .await;
+
+    let (_, body) = api(
+        &server,
+        "createAuthUri",
+        json!({ "identifier": "user@example.com", "continueUri": "http://localhost" }),
+    )
+    .await;
+    assert_eq!(body["registered"], true);
+    assert_eq!(body["signinMethods"], json!(["password"]));
+
+    let (_, body) = api(
+        &server,
+        "createAuthUri",
+        json!({ "identifier": "other@example.com", "continueUri": "http://localhost" }),
+    )
+    .await;
+    assert_eq!(body["registered"], false);
+}
+
+#[tokio::test]
+async fn test_mock_sign_in_with_idp() {
+    let server = MockServer::start().await.unwrap();
+
+    let (_, body) = api(
+        &server,
+        "createAuthUri",
+        json!({
+            "continueUri": "http://localhost/redirect",
+            "providerId": "google.com",
+            "authFlowType": "CODE_FLOW",
+            "context": "some-context",
+        }),
+    )
+    .await;
+    // The mock IdP redirects straight back with a code.
+    let auth_uri = body["authUri"].as_str().unwrap();
+    assert!(auth_uri.starts_with("http://localhost/redirect?code="));
+
+    let (status, body) = api(
+        &server,
+        "signInWithIdp",
+        json!({ "requestUri": auth_uri, "sessionId": body["sessionId"], "returnSecureToken": true }),
+    )
+    .await;
+    assert_eq!(status, 200);
+    assert_eq!(body["providerId"], "google.com");
+    assert_eq!(body["email"], "user@google.com");
+    assert_eq!(body["context"], "some-context");
+    assert_eq!(body["isNewUser"], true);
+
+    // The code can only be exchanged once.
+    let (status, _) = api(
+        &server,
+        "signInWithIdp",
+        json!({ "requestUri": auth_uri, "sessionId": body["sessionId"] }),
+    )
+    .await;
+    assert_eq!(status, 400);
+}
+
+#[tokio::test]
+async fn test_mock_sign_in_with_email_link() {
+    let server = MockServer::start().await.unwrap();
+
+    api(
+        &server,
+        "sendOobCode",
+        json!({
+            "requestType": "EMAIL_SIGNIN",
+            "email": "user@example.com",
+            "continueUrl": "http://localhost",
+        }),
+    )
+    .await;
+    let code = server.oob_codes().pop().unwrap();
+    assert_eq!(code.continue_url.as_deref(), Some("http://localhost"));
+    assert_eq!(
+        code.link(),
+        format!(
+            "http://localhost/?apiKey=any-api-key&mode=signIn&oobCode={}&continueUrl=http%3A%2F%2Flocalhost",
+            code.code
+        )
+    );
+
+    let (_, body) = api(
+        &server,
+        "signInWithEmailLink",
+        json!({ "email": "user@example.com", "oobCode": code.code }),
+    )
+    .await;
+    assert_eq!(body["isNewUser"], true);
+
+    let (_, lookup) = api(&server, "lookup", json!({ "idToken": body["idToken"] })).await;
+    assert_eq!(lookup["users"][0]["emailVerified"], true);
+}
+
+#[tokio::test]
+async fn test_mock_verify_email() {
+    let server = MockServer::start().await.unwrap();
+    let (_, user) = api(
+        &server,
+        "signUp",
+        json!({ "email": "user@example.com", "password": "password" }),
+    )
+    .await;
+
+    api(
+        &server,
+        "sendOobCode",
+        json!({ "requestType": "VERIFY_EMAIL", "idToken": user["idToken"] }),
+    )
+    .await;
+    let code = server.oob_codes().pop().unwrap();
+
+    // `accounts:resetPassword` without a new password only checks the code.
+    let (_, body) = api(&server, "resetPassword", json!({ "oobCode": code.code })).await;
+    assert_eq!(body["requestType"], "VERIFY_EMAIL");
+    assert_eq!(body["email"], "user@example.com");
+
+    let (_, body) = api(&server, "update", json!({ "oobCode": code.code })).await;
+    assert_eq!(body["emailVerified"], true);
+}
+
+#[tokio::test]
+async fn test_mock_verify_and_change_email() {
+    let server = MockServer::start().await.unwrap();
+    let (_, user) = api(
+        &server,
+        "signUp",
+        json!({ "email": "old@example.com", "password": "password" }),
+    )
+    .await;
+
+    api(
+        &server,
+        "sendOobCode",
+        json!({
+            "requestType": "VERIFY_AND_CHANGE_EMAIL",
+            "idToken": user["idToken"],
+            "newEmail": "new@example.com",
+        }),
+    )
+    .await;
+    let code = server.oob_codes().pop().unwrap();
+    assert_eq!(code.email, "old@example.com");
+    assert_eq!(code.new_email.as_deref(), Some("new@example.com"));
+
+    let (_, body) = api(&server, "resetPassword", json!({ "oobCode": code.code })).await;
+    assert_eq!(body["newEmail"], "new@example.com");
+
+    let (_, body) = api(&server, "update", json!({ "oobCode": code.code })).await;
+    assert_eq!(body["email"], "new@example.com");
+    assert_eq!(body["emailVerified"], true);
+
+    let (status, _) = api(
+        &server,
+        "signInWithPassword",
+        json!({ "email": "new@example.com", "password": "password" }),
+    )
+    .await;
+    assert_eq!(status, 200);
+}
+
+#[tokio::test]
+async fn test_mock_refresh_token() {
+    let server = MockServer::start().await.unwrap();
+    let (_, user) = api(
+        &server,
+        "signUp",
+        json!({ "email": "user@example.com", "password": "password" }),
+    )
+    .await;
+
+    let response = reqwest::Client::new()
+        .post(format!(
+            "http://{}/securetoken.googleapis.com/v1/token?key=any-api-key",
+            server.host()
+        ))
+        .body(
+            json!({ "grant_type": "refresh_token", "refresh_token": user["refreshToken"] })
+                .to_string(),
+        )
+        .send()
+        .await
+        .unwrap();
+    let body: Value = response.json().await.unwrap();
+
+    assert_eq!(body["user_id"], user["localId"]);
+    assert_eq!(body["refresh_token"], user["refreshToken"]);
+    assert!(body["id_token"].is_string());
+}
@@ -23,6 +23,8 @@
  * Settings object for the "startOauthFlow" method.
  * @typedef {Object} oauthFlowOptions
  * @property {string} provider Name of the provider to use.
+ * @property {string} [oauthScope] Space-delimited OAuth scopes to request besides the default ones.
+ * @property {Object.<string, string>} [customParameter] Custom OAuth parameters of the provider, like "prompt" or "login_hint".
  * @property {string} [context] A string that will be returned after the OAuth flow is finished should be used to retain context.
  * @property {boolean} [linkAccount = false] Check whether to link this OAuth account with the current account. Defaults to false.
  */
@@ -38,9 +40,20 @@ const storageApi = {};
  * @param {string} options.apiKey The Firebase API key.
  * @param {string} options.redirectUri The redirect URL used by OAuth providers.
  * @param {Array.<ProviderOptions|string>} options.providers Array of arguments that will be passed to the addProvider method.
+ * @param {boolean} [options.syncTabs = true] Check whether to pick up sign-ins and sign-outs from other tabs.
+ * @param {string} [options.identityToolkitUrl] Base URL of the "identitytoolkit" API, e.g. the Auth Emulator.
+ * @param {string} [options.secureTokenUrl] Base URL of the "securetoken" API, e.g. the Auth Emulator.
  */
 export class Auth {
-	constructor({ apiKey, redirectUri, name = 'default', storage = storageApi } = {}) {
+	constructor({
+		apiKey,
+		redirectUri,
+		name = 'default',
+		storage = storageApi,
+		syncTabs = true,
+		identityToolkitUrl = 'https://identitytoolkit.googleapis.com',
+		secureTokenUrl = 'https://securetoken.googleapis.com'
+	} = {}) {
 		if (!apiKey) throw Error('The argument "apiKey" is required');
 
 		Object.assign(this, {
@@ -48,7 +61,10 @@ export class Auth {
 			redirectUri,
 			name,
 			storage,
-			listeners: []
+			identityToolkitUrl,
+			secureTokenUrl,
+			listeners: [],
+			idTokenListeners: []
 		});
 
 		this.storage.get(this.sKey('User')).then(user => {
@@ -64,7 +80,9 @@ export class Auth {
 
 		// Because this library is also used in React Native, outside the browser as well,
 		// we need to check if this environment supports `addEventListener` on the window.
-		'addEventListener' in window &&
+		// Sessions that aren't kept in localStorage must not be replaced by the one from another tab.
+		syncTabs &&
+			'addEventListener' in window &&
 			window.addEventListener('storage', e => {
 				// This code will run if localStorage for this user
 				// data was updated from a different browser window.
@@ -83,6 +101,26 @@ export class Auth {
 		this.listeners.forEach(cb => cb(this.user));
 	}
 
+	/**
+	 * Triggers all of the ID token listeners.
+	 * @private
+	 */
+	emitIdToken() {
+		this.idTokenListeners.forEach(cb => cb(this.user));
+	}
+
+	/**
+	 * Sets up a function that will be called whenever the ID token changes,
+	 * on sign-in, sign-out and every token refresh.
+	 * @param {function} callback Function to call with the user when the ID token changes.
+	 * @returns {function} The function that unsubscribes your callback after being called.
+	 */
+	listenIdToken(cb) {
+		this.idTokenListeners.push(cb);
+
+		return () => (this.idTokenListeners = this.idTokenListeners.filter(fn => fn !== cb));
+	}
+
 	/**
 	 * Sets up a function that will be called whenever the user state is changed.
 	 * @param {function} callback Function to call when the event is triggered.
@@ -112,8 +150,8 @@ export class Auth {
 	api(endpoint, body) {
 		const url =
 			endpoint === 'token'
-				? `https://securetoken.googleapis.com/v1/token?key=${this.apiKey}`
-				: `https://identitytoolkit.googleapis.com/v1/accounts:${endpoint}?key=${this.apiKey}`;
+				? `${this.secureTokenUrl}/v1/token?key=${this.apiKey}`
+				: `${this.identityToolkitUrl}/v1/accounts:${endpoint}?key=${this.apiKey}`;
 
 		return fetch(url, {
 			method: 'POST',
@@ -153,9 +191,14 @@ export class Auth {
 	 * @private
 	 */
 	async setState(userData, persist = true, emit = true) {
+		const idToken = user => (user && user.tokenManager && user.tokenManager.idToken) || null;
+		const idTokenChanged = idToken(this.user) !== idToken(userData);
+
 		this.user = userData;
 		persist && (await this.storage[userData ? 'set' : 'remove'](this.sKey('User'), JSON.stringify(userData)));
 		emit && this.emit();
+		// Token refreshes don't emit an auth state change, but they do change the ID token.
+		idTokenChanged && this.emitIdToken();
 	}
 
 	/**
@@ -242,7 +285,7 @@ export class Auth {
 			);
 
 		// The options can be a string, or an object, so here we make sure we extract the right data in each case.
-		const { provider, oauthScope, context, linkAccount } =
+		const { provider, oauthScope, customParameter, context, linkAccount } =
 			typeof options === 'string' ? { provider: options } : options;
 
 		// Make sure the user is logged in when an "account link" was requested.
@@ -254,6 +297,7 @@ export class Auth {
 			authFlowType: 'CODE_FLOW',
 			providerId: provider,
 			oauthScope,
+			customParameter,
 			context
 		});
 
@@ -315,18 +359,41 @@ export class Auth {
 		// Email sign-in flow.
 		if (location.href.match(/[&?]oobCode=/)) {
 			const oobCode = location.href.match(/[?&]oobCode=([^&]+)/)[1];
-			const email = (options && options.email) || location.href.match(/[?&]email=([^&]+)/)[1];
-			const expiresAt = Date.now() + 3600 * 1000;
-			const { idToken, refreshToken } = await this.api('signInWithEmailLink', { oobCode, email });
+			const emailParam = location.href.match(/[?&]email=([^&]+)/);
+			const email = (options && options.email) || (emailParam && decodeURIComponent(emailParam[1]));
 
-			// Now, get the user profile.
-			await this.fetchProfile({ idToken, refreshToken, expiresAt });
+			await this.signInWithEmailLink(oobCode, email);
 
 			// Remove sensitive data from the URLSearch params in the location bar.
 			history.replaceState(null, null, location.origin + location.pathname);
 		}
 	}
 
+	/**
+	 * Sends a sign-in link to the email and remembers it,
+	 * so the sign-in can be completed without asking for the email again.
+	 * @param {string} email
+	 * @param {Object} [actionCodeSettings] Where the link leads.
+	 */
+	async sendSignInLink(email, actionCodeSettings) {
+		await this.sendOobCode('EMAIL_SIGNIN', email, actionCodeSettings);
+		await this.storage.set(this.sKey('EmailForSignIn'), email);
+	}
+
+	/**
+	 * Signs in with the oobCode of a sign-in link.
+	 * @param {string} oobCode
+	 * @param {string} [email] Defaults to the email remembered by `sendSignInLink`.
+	 */
+	async signInWithEmailLink(oobCode, email) {
+		email = email || (await this.storage.get(this.sKey('EmailForSignIn')));
+		if (!email) throw Error('MISSING_EMAIL');
+
+		const user = await this.fetchProfile(await this.api('signInWithEmailLink', { oobCode, email }));
+		await this.storage.remove(this.sKey('EmailForSignIn'));
+		return user;
+	}
+
 	/**
 	 * Signs up with email and password or anonymously when no arguments are passed.
 	 * Automatically signs the user in on completion.
@@ -362,24 +429,30 @@ export class Auth {
 
 	/**
 	 * Sends an out-of-band confirmation code for an account.
-	 * It can be used to reset a password, to verify an email address and send a sign-in email link.
+	 * It can be used to reset a password, to verify an email address, to send a sign-in email link
+	 * and to change the email address once the new one is verified.
 	 * The email argument is not needed if verifying an email (the argument is ignored). Otherwise, it is required.
-	 * @param {'PASSWORD_RESET'|'VERIFY_EMAIL'|'EMAIL_SIGNIN'} requestType The type of out-of-band (OOB) code to send.
-	 * @param {string} [email] When the `requestType` is `PASSWORD_RESET` or `EMAIL_SIGNIN` you need to provide an email address.
-	 * @returns {Promise}
+	 * @param {'PASSWORD_RESET'|'VERIFY_EMAIL'|'EMAIL_SIGNIN'|'VERIFY_AND_CHANGE_EMAIL'} requestType The type of out-of-band (OOB) code to send.
+	 * @param {string} [email] When the `requestType` is `PASSWORD_RESET` or `EMAIL_SIGNIN` you need to provide an email address,
+	 * for `VERIFY_AND_CHANGE_EMAIL` the new email address.
+	 * @param {Object} [actionCodeSettings] Where the link leads, defaults to the `redirectUri` with the email as a query parameter.
 	 */
-	async sendOobCode(requestType, email) {
+	async sendOobCode(requestType, email, actionCodeSettings) {
 		const verifyEmail = requestType === 'VERIFY_EMAIL';
-		if (verifyEmail) {
+		const changeEmail = requestType === 'VERIFY_AND_CHANGE_EMAIL';
+		if (verifyEmail || changeEmail) {
 			await this.enforceAuth();
+		}
+		if (verifyEmail) {
 			email = this.user.email;
 		}
 
-		return void this.api('sendOobCode', {
-			idToken: verifyEmail ? this.user.tokenManager.idToken : undefined,
+		await this.api('sendOobCode', {
+			idToken: verifyEmail || changeEmail ? this.user.tokenManager.idToken : undefined,
 			requestType,
-			email,
-			continueUrl: this.redirectUri + `?email=${email}`
+			email: changeEmail ? undefined : email,
+			newEmail: changeEmail ? email : undefined,
+			...(actionCodeSettings || { continueUrl: this.redirectUri + `?email=${email}` })
 		});
 	}
 
@@ -393,6 +466,24 @@ export class Auth {
 		return (await this.api('resetPassword', { oobCode, newPassword })).email;
 	}
 
+	/**
+	 * Returns what an oobCode does and who it was sent to, without using it up.
+	 * @param {string} oobCode
+	 * @returns {Object} The `requestType`, `email` and, for email changes, `newEmail` of the code.
+	 */
+	async checkActionCode(oobCode) {
+		const { requestType, email, newEmail } = await this.api('resetPassword', { oobCode });
+		return { requestType, email, newEmail };
+	}
+
+	/**
+	 * Applies an oobCode that verifies or changes an email.
+	 * @param {string} oobCode
+	 */
+	async applyActionCode(oobCode) {
+		return void (await this.api('update', { oobCode }));
+	}
+
 	/**
 	 * Returns info about all providers associated with a specified email.
 	 * @param {string} email The user's email address.
@@ -421,9 +512,21 @@ export class Auth {
 		return userData;
 	}
 
+	/**
+	 * Links an email and password to the signed-in user, e.g. to turn an anonymous user
+	 * into a permanent account. The uid stays the same.
+	 * @param {string} email
+	 * @param {string} password
+	 * @throws Will throw if the user is not signed-in.
+	 */
+	async linkWithEmailPassword(email, password) {
+		return await this.updateProfile({ email, password });
+	}
+
 	/**
 	 * Updates the user's profile.
 	 * @param {Object} newData An object with the new data.
+	 * @returns {Object} The updated user.
 	 * @throws Will throw if the user is not signed-in.
 	 */
 	async updateProfile(newData) {
@@ -449,6 +552,7 @@ export class Auth {
 		delete updatedData.refreshToken;
 
 		await this.setState(updatedData);
+		return this.user;
 	}
 
 	/**
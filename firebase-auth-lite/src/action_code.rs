@@ -0,0 +1,168 @@
+use crate::AuthError;
+use serde::{Deserialize, Serialize};
+use std::{collections::HashMap, fmt};
+use url::Url;
+
+/// The kind of out-of-band email sent by `send_oob_code`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum OobRequest {
+    /// A link to reset the password of `email`.
+    PasswordReset,
+    /// A link to verify the email of the signed-in user, the email argument is ignored.
+    VerifyEmail,
+    /// A link to sign in as `email` without a password.
+    EmailSignIn,
+    /// A link sent to the new `email`, that changes the email of the
+    /// signed-in user once it is verified.
+    VerifyAndChangeEmail,
+}
+
+impl OobRequest {
+    /// The `requestType` of the `accounts:sendOobCode` request.
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            OobRequest::PasswordReset => "PASSWORD_RESET",
+            OobRequest::VerifyEmail => "VERIFY_EMAIL",
+            OobRequest::EmailSignIn => "EMAIL_SIGNIN",
+            OobRequest::VerifyAndChangeEmail => "VERIFY_AND_CHANGE_EMAIL",
+        }
+    }
+}
+
+impl fmt::Display for OobRequest {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+/// What an action code does, as returned by [`check_action_code`](crate::Auth::check_action_code).
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "SCREAMING_SNAKE_CASE")]
+pub enum ActionCodeOperation {
+    PasswordReset,
+    VerifyEmail,
+    /// Changes the email back, after it was changed from another device.
+    RecoverEmail,
+    #[serde(rename = "EMAIL_SIGNIN")]
+    EmailSignIn,
+    VerifyAndChangeEmail,
+    RevertSecondFactorAddition,
+    /// An operation added to the API after this crate.
+    #[serde(other)]
+    Unknown,
+}
+
+/// The details of an action code, without applying it.
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ActionCodeInfo {
+    #[serde(rename = "requestType")]
+    pub operation: ActionCodeOperation,
+    /// The email the code was sent to.
+    pub email: String,
+    /// For `VerifyAndChangeEmail` the email it changes to, for `RecoverEmail`
+    /// the email it changes back from.
+    #[serde(default)]
+    pub new_email: Option<String>,
+}
+
+/// Where the link of an out-of-band email leads, e.g. straight into a mobile app.
+///
+/// ```
+/// use firebase_auth_lite::ActionCodeSettings;
+///
+/// let settings = ActionCodeSettings::new("https://example.com/finish-sign-in")
+///     .with_handle_code_in_app(true)
+///     .with_ios_bundle_id("com.example.ios")
+///     .with_android_package_name("com.example.android", true);
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ActionCodeSettings {
+    continue_url: String,
+    #[serde(skip_serializing_if = "std::ops::Not::not")]
+    can_handle_code_in_app: bool,
+    #[serde(rename = "iOSBundleId", skip_serializing_if = "Option::is_none")]
+    ios_bundle_id: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    android_package_name: Option<String>,
+    #[serde(skip_serializing_if = "std::ops::Not::not")]
+    android_install_app: bool,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    dynamic_link_domain: Option<String>,
+}
+
+impl ActionCodeSettings {
+    /// `continue_url` is where the user ends up after the action, it has to be
+    /// on an authorized domain of the project.
+    pub fn new(continue_url: &str) -> Self {
+        ActionCodeSettings {
+            continue_url: continue_url.into(),
+            can_handle_code_in_app: false,
+            ios_bundle_id: None,
+            android_package_name: None,
+            android_install_app: false,
+            dynamic_link_domain: None,
+        }
+    }
+
+    /// Opens the link in the app, or at `continue_url`, instead of the Firebase action handler.
+    /// Email link sign-in requires it.
+    pub fn with_handle_code_in_app(mut self, handle_code_in_app: bool) -> Self {
+        self.can_handle_code_in_app = handle_code_in_app;
+        self
+    }
+
+    pub fn with_ios_bundle_id(mut self, bundle_id: &str) -> Self {
+        self.ios_bundle_id = Some(bundle_id.into());
+        self
+    }
+
+    /// Opens the link in the Android app, `install_app` offers to install it first.
+    pub fn with_android_package_name(mut self, package_name: &str, install_app: bool) -> Self {
+        self.android_package_name = Some(package_name.into());
+        self.android_install_app = install_app;
+        self
+    }
+
+    /// The Dynamic Links domain to use when the project has several.
+    pub fn with_dynamic_link_domain(mut self, domain: &str) -> Self {
+        self.dynamic_link_domain = Some(domain.into());
+        self
+    }
+
+    pub fn continue_url(&self) -> &str {
+        &self.continue_url
+    }
+}
+
+/// The query parameters of an email action link, e.g. its `oobCode`.
+///
+/// A link wrapped by Firebase Dynamic Links carries the action link in its `link` parameter.
+pub(crate) fn action_link_params(link: &str) -> Option<HashMap<String, String>> {
+    let url = Url::parse(link).ok()?;
+    let params: HashMap<String, String> = url.query_pairs().into_owned().collect();
+    if params.contains_key("oobCode") {
+        return Some(params);
+    }
+
+    let nested = params.get("link").or_else(|| params.get("deep_link_id"))?;
+    action_link_params(nested)
+}
+
+/// The `oobCode` of an email sign-in link, `None` for any other link.
+pub(crate) fn sign_in_oob_code(link: &str) -> Option<String> {
+    let mut params = action_link_params(link)?;
+    if params.get("mode").map(String::as_str) != Some("signIn") {
+        return None;
+    }
+    params.remove("oobCode")
+}
+
+/// The email of a `PasswordReset` code, any other code is invalid for resetting the password.
+pub(crate) fn password_reset_email(info: ActionCodeInfo) -> Result<String, AuthError> {
+    match info.operation {
+        ActionCodeOperation::PasswordReset => Ok(info.email),
+        _ => Err(AuthError::InvalidOobCode),
+    }
+}
@@ -0,0 +1,146 @@
+use crate::{
+    from_js, Auth, AuthError, AuthStateStream, IdToken, IdTokenStream, ProfileUpdate, UserProfile,
+};
+use async_trait::async_trait;
+use futures::Stream;
+
+/// The operations shared by the browser `Auth` and `native::Auth`, so
+/// business logic can be written once and tested against a mock.
+#[async_trait(?Send)]
+pub trait AuthClient {
+    async fn sign_in(&self, email: String, password: String) -> Result<UserProfile, AuthError>;
+
+    async fn sign_up(&self, email: String, password: String) -> Result<UserProfile, AuthError>;
+
+    /// Signs in as a new anonymous user, e.g. a guest that registers later.
+    async fn sign_in_anonymously(&self) -> Result<UserProfile, AuthError>;
+
+    async fn sign_out(&self) -> Result<(), AuthError>;
+
+    /// The signed-in user, without making a request.
+    ///
+    /// A session that can't be read is an error, not signed out.
+    fn current_user(&self) -> Result<Option<UserProfile>, AuthError>;
+
+    /// The ID token of the signed-in user, refreshed first if it has expired.
+    async fn id_token(&self) -> Result<IdToken, AuthError> {
+        self.get_id_token(false).await
+    }
+
+    /// The ID token of the signed-in user, refreshed first if it has expired or `force_refresh`,
+    /// e.g. to pick up custom claims that were just set.
+    async fn get_id_token(&self, force_refresh: bool) -> Result<IdToken, AuthError>;
+
+    /// Applies `update` to the signed-in user and returns the updated profile.
+    async fn update_profile(&self, update: ProfileUpdate) -> Result<UserProfile, AuthError>;
+
+    /// Links an email and password to the signed-in user, e.g. to turn an
+    /// anonymous user into a permanent account with the same uid.
+    async fn link_with_email_password(
+        &self,
+        email: String,
+        password: String,
+    ) -> Result<UserProfile, AuthError>;
+
+    async fn delete_account(&self) -> Result<(), AuthError>;
+}
+
+/// The streams of the browser `Auth` and `testing::FakeAuth`, kept apart from
+/// `AuthClient` since `native::Auth` has no listeners.
+pub trait AuthStreams {
+    type AuthStateStream: Stream<Item = Result<Option<UserProfile>, AuthError>>;
+    type IdTokenStream: Stream<Item = Result<Option<IdToken>, AuthError>>;
+
+    /// A `Stream` of the signed-in user, starting with the current one.
+    fn auth_state_stream(&self) -> Self::AuthStateStream;
+
+    /// A `Stream` of the ID token, starting with the current one.
+    fn id_token_stream(&self) -> Self::IdTokenStream;
+}
+
+impl Auth {
+    /// The signed-in user, without making a request.
+    ///
+    /// A session that can't be read is an error, not signed out.
+    pub fn current_user(&self) -> Result<Option<UserProfile>, AuthError> {
+        from_js(self.js_user())
+    }
+
+    /// The ID token of the signed-in user, refreshed first if it has expired.
+    pub async fn id_token(&self) -> Result<IdToken, AuthError> {
+        self.get_id_token(false).await
+    }
+
+    /// The ID token of the signed-in user, refreshed first if it has expired or `force_refresh`,
+    /// e.g. to pick up custom claims that were just set.
+    pub async fn get_id_token(&self, force_refresh: bool) -> Result<IdToken, AuthError> {
+        // `refreshIdToken` throws a `TypeError` when signed-out, `enforceAuth` has a proper error.
+        self.js_enforce_auth().await?;
+        if force_refresh {
+            self.js_refresh_id_token(true).await?;
+        }
+
+        let id_token = self
+            .current_user()?
+            .and_then(|user| user.token_manager)
+            .map(|token_manager| token_manager.id_token)
+            .ok_or(AuthError::NotSignedIn)?;
+        IdToken::decode(&id_token)
+    }
+}
+
+#[async_trait(?Send)]
+impl AuthClient for Auth {
+    async fn sign_in(&self, email: String, password: String) -> Result<UserProfile, AuthError> {
+        Auth::sign_in(self, email, password).await
+    }
+
+    async fn sign_up(&self, email: String, password: String) -> Result<UserProfile, AuthError> {
+        Auth::sign_up(self, email, password).await
+    }
+
+    async fn sign_in_anonymously(&self) -> Result<UserProfile, AuthError> {
+        Auth::sign_in_anonymously(self).await
+    }
+
+    async fn sign_out(&self) -> Result<(), AuthError> {
+        Auth::sign_out(self).await
+    }
+
+    fn current_user(&self) -> Result<Option<UserProfile>, AuthError> {
+        Auth::current_user(self)
+    }
+
+    async fn get_id_token(&self, force_refresh: bool) -> Result<IdToken, AuthError> {
+        Auth::get_id_token(self, force_refresh).await
+    }
+
+    async fn update_profile(&self, update: ProfileUpdate) -> Result<UserProfile, AuthError> {
+        Auth::update_profile(self, update).await
+    }
+
+    async fn link_with_email_password(
+        &self,
+        email: String,
+        password: String,
+    ) -> Result<UserProfile, AuthError> {
+        Auth::link_with_email_password(self, email, password).await
+    }
+
+    async fn delete_account(&self) -> Result<(), AuthError> {
+        Auth::delete_account(self).await
+    }
+}
+
+impl AuthStreams for Auth {
+    type AuthStateStream = AuthStateStream;
+    type IdTokenStream = IdTokenStream;
+
+    fn auth_state_stream(&self) -> AuthStateStream {
+        Auth::auth_state_stream(self)
+    }
+
+    fn id_token_stream(&self) -> IdTokenStream {
+        Auth::id_token_stream(self)
+    }
+}
@@ -0,0 +1,119 @@
+use crate::{Auth, AuthError};
+use serde::{de::DeserializeOwned, Serialize};
+use wasm_bindgen::{JsCast, JsValue};
+use wasm_bindgen_futures::JsFuture;
+use web_sys::{Request, RequestInit, Response};
+
+/// An HTTP client that adds the `Authorization: Bearer <idToken>` header of
+/// the signed-in user to every request, refreshing the ID token when it has expired.
+///
+/// Requests are sent without the header when no user is signed-in.
+#[derive(Clone)]
+pub struct AuthorizedClient {
+    auth: Auth,
+    base_url: String,
+    headers: Vec<(String, String)>,
+}
+
+impl AuthorizedClient {
+    pub fn new(auth: Auth) -> Self {
+        AuthorizedClient {
+            auth,
+            base_url: String::new(),
+            headers: Vec::new(),
+        }
+    }
+
+    /// Prefixes the path of every request with `base_url`.
+    pub fn with_base_url(mut self, base_url: &str) -> Self {
+        self.base_url = base_url.trim_end_matches('/').into();
+        self
+    }
+
+    /// Adds a header to every request.
+    pub fn with_header(mut self, name: &str, value: &str) -> Self {
+        self.headers.push((name.into(), value.into()));
+        self
+    }
+
+    pub async fn get<T: DeserializeOwned>(&self, path: &str) -> Result<T, AuthError> {
+        self.json("GET", path, None::<&()>).await
+    }
+
+    pub async fn post<B, T>(&self, path: &str, body: &B) -> Result<T, AuthError>
+    where
+        B: Serialize + ?Sized,
+        T: DeserializeOwned,
+    {
+        self.json("POST", path, Some(body)).await
+    }
+
+    pub async fn put<B, T>(&self, path: &str, body: &B) -> Result<T, AuthError>
+    where
+        B: Serialize + ?Sized,
+        T: DeserializeOwned,
+    {
+        self.json("PUT", path, Some(body)).await
+    }
+
+    pub async fn delete<T: DeserializeOwned>(&self, path: &str) -> Result<T, AuthError> {
+        self.json("DELETE", path, None::<&()>).await
+    }
+
+    /// Sends a request built by the caller, only adding the client headers
+    /// and the `Authorization` header.
+    pub async fn send(&self, request: Request) -> Result<Response, AuthError> {
+        let headers = request.headers();
+        for (name, value) in &self.headers {
+            headers.set(name, value)?;
+        }
+
+        Ok(self
+            .auth
+            .js_authorized_request(request)
+            .await?
+            .unchecked_into())
+    }
+
+    async fn json<B, T>(&self, method: &str, path: &str, body: Option<&B>) -> Result<T, AuthError>
+    where
+        B: Serialize + ?Sized,
+        T: DeserializeOwned,
+    {
+        let init = RequestInit::new();
+        init.set_method(method);
+
+        let request = match body {
+            Some(body) => {
+                let body =
+                    serde_json::to_string(body).map_err(|e| AuthError::Unknown(e.to_string()))?;
+                init.set_body(&JsValue::from_str(&body));
+                let request = Request::new_with_str_and_init(&self.url(path), &init)?;
+                request.headers().set("Content-Type", "application/json")?;
+                request
+            }
+            None => Request::new_with_str_and_init(&self.url(path), &init)?,
+        };
+
+        let response = self.send(request).await?;
+        let text = JsFuture::from(response.text()?)
+            .await?
+            .as_string()
+            .unwrap_or_default();
+
+        if !response.ok() {
+            return Err(AuthError::Http {
+                status: response.status(),
+                body: text,
+            });
+        }
+
+        // Treat an empty body like `null`, so `()` can be used for `204 No Content`.
+        let text = if text.is_empty() { "null" } else { &text };
+        serde_json::from_str(text).map_err(|e| AuthError::InvalidResponse(e.to_string()))
+    }
+
+    fn url(&self, path: &str) -> String {
+        format!("{}{}", self.base_url, path)
+    }
+}
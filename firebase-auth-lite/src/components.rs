@@ -0,0 +1,211 @@
+//! Yew components for a custom email action handler page.
+//!
+//! Point the action URL of the project's email templates at a route that
+//! renders [`EmailActionHandler`].
+
+// The `html!` macro of yew 0.19 expands to code that newer clippy lints flag.
+#![allow(clippy::let_unit_value, clippy::unnecessary_operation)]
+
+use crate::{Auth, AuthError, EmailAction, EmailActionOutcome};
+use web_sys::HtmlInputElement;
+use yew::prelude::*;
+
+#[derive(Properties, PartialEq)]
+pub struct EmailActionHandlerProps {
+    pub auth: Auth,
+    /// The action link, the current location by default.
+    #[prop_or_default]
+    pub link: Option<String>,
+}
+
+/// Handles the email action link it is opened with, e.g. verifies the email,
+/// and shows the result.
+pub struct EmailActionHandler {
+    action: Option<EmailAction>,
+    outcome: Option<Result<EmailActionOutcome, AuthError>>,
+}
+
+pub enum EmailActionHandlerMsg {
+    Handled(Result<EmailActionOutcome, AuthError>),
+}
+
+impl Component for EmailActionHandler {
+    type Message = EmailActionHandlerMsg;
+    type Properties = EmailActionHandlerProps;
+
+    fn create(ctx: &Context<Self>) -> Self {
+        let action = match &ctx.props().link {
+            Some(link) => EmailAction::parse(link),
+            None => EmailAction::from_location(),
+        };
+
+        if let Some(action) = action.clone() {
+            let auth = ctx.props().auth.clone();
+            ctx.link().send_future(async move {
+                EmailActionHandlerMsg::Handled(auth.handle_email_action(&action).await)
+            });
+        }
+
+        EmailActionHandler {
+            action,
+            outcome: None,
+        }
+    }
+
+    fn update(&mut self, _ctx: &Context<Self>, msg: EmailActionHandlerMsg) -> bool {
+        match msg {
+            EmailActionHandlerMsg::Handled(outcome) => {
+                self.outcome = Some(outcome);
+                true
+            }
+        }
+    }
+
+    fn view(&self, ctx: &Context<Self>) -> Html {
+        let action = match &self.action {
+            Some(action) => action,
+            None => return html! { <p>{ "This link is invalid." }</p> },
+        };
+
+        let message = match &self.outcome {
+            None => return html! { <p>{ "Working on it" }</p> },
+            Some(Ok(EmailActionOutcome::ResetPassword { email })) => {
+                return html! {
+                    <ResetPasswordForm
+                        auth={ctx.props().auth.clone()}
+                        oob_code={action.oob_code.clone()}
+                        email={email.clone()}
+                    />
+                }
+            }
+            Some(Ok(EmailActionOutcome::EmailVerified { email })) => {
+                format!("{} has been verified.", email)
+            }
+            Some(Ok(EmailActionOutcome::EmailRecovered { email })) => format!(
+                "The email has been changed back to {}, consider resetting the password too.",
+                email
+            ),
+            Some(Ok(EmailActionOutcome::EmailChanged { email })) => {
+                format!("The email has been changed to {}.", email)
+            }
+            Some(Ok(EmailActionOutcome::SignedIn(user))) => match &user.email {
+                Some(email) => format!("Signed in as {}.", email),
+                None => "Signed in.".into(),
+            },
+            Some(Err(AuthError::InvalidOobCode)) | Some(Err(AuthError::ExpiredOobCode)) => {
+                "This link has expired or has already been used.".into()
+            }
+            Some(Err(error)) => error.to_string(),
+        };
+
+        html! {
+            <>
+            <p>{ message }</p>
+            { continue_link(action) }
+            </>
+        }
+    }
+}
+
+#[derive(Properties, PartialEq)]
+pub struct ResetPasswordFormProps {
+    pub auth: Auth,
+    pub oob_code: String,
+    /// The email of the account, from `verify_password_reset_code`.
+    pub email: String,
+}
+
+/// Asks for a new password and resets it with `oob_code`.
+pub struct ResetPasswordForm {
+    password: String,
+    reset_in_progress: bool,
+    result: Option<Result<String, AuthError>>,
+}
+
+pub enum ResetPasswordFormMsg {
+    PasswordInput(String),
+    Reset,
+    Done(Result<String, AuthError>),
+}
+
+impl Component for ResetPasswordForm {
+    type Message = ResetPasswordFormMsg;
+    type Properties = ResetPasswordFormProps;
+
+    fn create(_ctx: &Context<Self>) -> Self {
+        ResetPasswordForm {
+            password: String::new(),
+            reset_in_progress: false,
+            result: None,
+        }
+    }
+
+    fn update(&mut self, ctx: &Context<Self>, msg: ResetPasswordFormMsg) -> bool {
+        match msg {
+            ResetPasswordFormMsg::PasswordInput(password) => {
+                self.password = password;
+                false
+            }
+            ResetPasswordFormMsg::Reset => {
+                let auth = ctx.props().auth.clone();
+                let oob_code = ctx.props().oob_code.clone();
+                let password = self.password.clone();
+                ctx.link().send_future(async move {
+                    ResetPasswordFormMsg::Done(auth.reset_password(oob_code, password).await)
+                });
+
+                self.reset_in_progress = true;
+                true
+            }
+            ResetPasswordFormMsg::Done(result) => {
+                self.reset_in_progress = false;
+                self.result = Some(result);
+                true
+            }
+        }
+    }
+
+    fn view(&self, ctx: &Context<Self>) -> Html {
+        if self.reset_in_progress {
+            return html! { <p>{ "Working on it" }</p> };
+        }
+        if let Some(Ok(email)) = &self.result {
+            return html! {
+                <p>{ format!("The password of {} has been changed.", email) }</p>
+            };
+        }
+
+        let on_input_password = ctx.link().callback(|evt: InputEvent| {
+            let input: HtmlInputElement = evt.target_unchecked_into();
+            ResetPasswordFormMsg::PasswordInput(input.value())
+        });
+        let on_click_reset = ctx.link().callback(|_| ResetPasswordFormMsg::Reset);
+        let error = match &self.result {
+            Some(Err(AuthError::WeakPassword)) => html! {
+                <p style="color:red;">{ "The password is too weak." }</p>
+            },
+            Some(Err(error)) => html! { <p style="color:red;">{ error.to_string() }</p> },
+            _ => html! {},
+        };
+
+        html! {
+            <>
+            <p>{ format!("Reset the password of {}", ctx.props().email) }</p>
+            <div>
+                <label>{ "New password" }
+                    <input type = "password" oninput = {on_input_password} name="password" />
+                </label>
+            </div>
+            { error }
+            <button onclick = {on_click_reset}>{ "Save" }</button>
+            </>
+        }
+    }
+}
+
+fn continue_link(action: &EmailAction) -> Html {
+    match &action.continue_url {
+        Some(url) => html! { <a href={url.clone()}>{ "Continue" }</a> },
+        None => html! {},
+    }
+}
@@ -0,0 +1,167 @@
+use crate::{
+    action_code, from_js, ActionCodeInfo, ActionCodeOperation, Auth, AuthError, UserProfile,
+};
+use async_trait::async_trait;
+
+/// The `mode` of an email action link.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum EmailActionMode {
+    ResetPassword,
+    VerifyEmail,
+    RecoverEmail,
+    SignIn,
+    VerifyAndChangeEmail,
+}
+
+impl EmailActionMode {
+    fn from_param(mode: &str) -> Option<Self> {
+        match mode {
+            "resetPassword" => Some(EmailActionMode::ResetPassword),
+            "verifyEmail" => Some(EmailActionMode::VerifyEmail),
+            "recoverEmail" => Some(EmailActionMode::RecoverEmail),
+            "signIn" => Some(EmailActionMode::SignIn),
+            "verifyAndChangeEmail" => Some(EmailActionMode::VerifyAndChangeEmail),
+            _ => None,
+        }
+    }
+}
+
+/// An email action link as opened on a custom email action handler page, e.g.
+/// `https://example.com/auth/action?mode=resetPassword&oobCode=...&apiKey=...`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct EmailAction {
+    pub mode: EmailActionMode,
+    pub oob_code: String,
+    /// Where to send the user afterwards, the `continueUrl` of the `ActionCodeSettings`.
+    pub continue_url: Option<String>,
+    /// The language of the email, e.g. to show the page in the same one.
+    pub lang: Option<String>,
+}
+
+/// What [`Auth::handle_email_action`] did.
+#[derive(Debug, Clone, PartialEq)]
+pub enum EmailActionOutcome {
+    /// The code is valid, the page should ask for a new password and call `reset_password`.
+    ResetPassword {
+        email: String,
+    },
+    EmailVerified {
+        email: String,
+    },
+    /// The email was changed back to `email`, the user should also reset the password.
+    EmailRecovered {
+        email: String,
+    },
+    /// The email was changed to `email`.
+    EmailChanged {
+        email: String,
+    },
+    SignedIn(Box<UserProfile>),
+}
+
+impl EmailAction {
+    /// Parses an action link, `None` if it isn't one or has an unknown `mode`.
+    pub fn parse(link: &str) -> Option<Self> {
+        let mut params = action_code::action_link_params(link)?;
+        let mode = EmailActionMode::from_param(params.get("mode")?)?;
+
+        Some(EmailAction {
+            mode,
+            oob_code: params.remove("oobCode")?,
+            continue_url: params.remove("continueUrl"),
+            lang: params.remove("lang"),
+        })
+    }
+
+    /// Parses the current location of the page.
+    pub fn from_location() -> Option<Self> {
+        let link = web_sys::window()?.location().href().ok()?;
+        EmailAction::parse(&link)
+    }
+}
+
+/// The action code calls of the browser `Auth` and `native::Auth`, that
+/// `handle_email_action` dispatches to.
+#[async_trait(?Send)]
+pub(crate) trait ActionCodes {
+    async fn check_action_code(&self, oob_code: String) -> Result<ActionCodeInfo, AuthError>;
+
+    async fn apply_action_code(&self, oob_code: String) -> Result<(), AuthError>;
+
+    async fn verify_password_reset_code(&self, oob_code: String) -> Result<String, AuthError>;
+
+    /// Signs in with the email remembered by `send_sign_in_link`.
+    async fn sign_in_with_email_link(&self, oob_code: String) -> Result<UserProfile, AuthError>;
+}
+
+/// Makes the call for `action`, except resetting the password which needs the new one.
+pub(crate) async fn handle_email_action<A: ActionCodes>(
+    auth: &A,
+    action: &EmailAction,
+) -> Result<EmailActionOutcome, AuthError> {
+    let oob_code = action.oob_code.clone();
+
+    let outcome = match action.mode {
+        EmailActionMode::ResetPassword => EmailActionOutcome::ResetPassword {
+            email: auth.verify_password_reset_code(oob_code).await?,
+        },
+        EmailActionMode::SignIn => {
+            EmailActionOutcome::SignedIn(Box::new(auth.sign_in_with_email_link(oob_code).await?))
+        }
+        _ => {
+            let info = auth.check_action_code(oob_code.clone()).await?;
+            let outcome = applied_outcome(info)?;
+            auth.apply_action_code(oob_code).await?;
+            outcome
+        }
+    };
+    Ok(outcome)
+}
+
+impl Auth {
+    /// Makes the call for `action`, except resetting the password which needs the new one.
+    ///
+    /// Email links are completed with the email saved by `send_sign_in_link`.
+    pub async fn handle_email_action(
+        &self,
+        action: &EmailAction,
+    ) -> Result<EmailActionOutcome, AuthError> {
+        handle_email_action(self, action).await
+    }
+}
+
+#[async_trait(?Send)]
+impl ActionCodes for Auth {
+    async fn check_action_code(&self, oob_code: String) -> Result<ActionCodeInfo, AuthError> {
+        Auth::check_action_code(self, oob_code).await
+    }
+
+    async fn apply_action_code(&self, oob_code: String) -> Result<(), AuthError> {
+        Auth::apply_action_code(self, oob_code).await
+    }
+
+    async fn verify_password_reset_code(&self, oob_code: String) -> Result<String, AuthError> {
+        Auth::verify_password_reset_code(self, oob_code).await
+    }
+
+    async fn sign_in_with_email_link(&self, oob_code: String) -> Result<UserProfile, AuthError> {
+        from_js(self.js_sign_in_with_email_link(oob_code, None).await?)
+    }
+}
+
+/// What applying a code does, from what `check_action_code` says about it.
+/// Codes that can't be applied are invalid.
+fn applied_outcome(info: ActionCodeInfo) -> Result<EmailActionOutcome, AuthError> {
+    match info.operation {
+        ActionCodeOperation::VerifyEmail => {
+            Ok(EmailActionOutcome::EmailVerified { email: info.email })
+        }
+        ActionCodeOperation::RecoverEmail => {
+            Ok(EmailActionOutcome::EmailRecovered { email: info.email })
+        }
+        ActionCodeOperation::VerifyAndChangeEmail => Ok(EmailActionOutcome::EmailChanged {
+            email: info.new_email.unwrap_or(info.email),
+        }),
+        _ => Err(AuthError::InvalidOobCode),
+    }
+}
@@ -0,0 +1,220 @@
+use std::fmt;
+use wasm_bindgen::{JsCast, JsValue};
+
+const NOT_SIGNED_IN: &str = "The user must be signed-in to use this method.";
+const LINK_ACCOUNT_SIGNED_OUT: &str =
+    "Request to \"Link account\" was made, but user is no longer signed-in";
+const MISSING_REDIRECT_URI: &str = "In order to use an Identity provider, you should initiate the \"Auth\" instance with a \"redirectUri\".";
+const MISSING_API_KEY: &str = "The argument \"apiKey\" is required";
+const INVALID_API_KEY: &str = "API key not valid";
+/// The messages of the `TypeError` that `fetch` rejects with in Chrome, Firefox and Safari.
+const FETCH_FAILED: [&str; 3] = [
+    "Failed to fetch",
+    "NetworkError when attempting to fetch resource",
+    "Load failed",
+];
+
+/// Errors returned by the `Auth` methods.
+///
+/// Most variants correspond to an error code returned by the identitytoolkit
+/// and securetoken REST APIs, the rest are raised by `main.js` itself.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum AuthError {
+    /// `EMAIL_EXISTS`: the email address is already in use by another account.
+    EmailExists,
+    /// `EMAIL_NOT_FOUND`: there is no user record corresponding to this email.
+    EmailNotFound,
+    /// `INVALID_PASSWORD`: the password is invalid or the user does not have a password.
+    InvalidPassword,
+    /// `INVALID_LOGIN_CREDENTIALS`: the email or password is wrong.
+    InvalidLoginCredentials,
+    /// `INVALID_EMAIL`: the email address is badly formatted.
+    InvalidEmail,
+    /// `MISSING_EMAIL`: an email address is required.
+    MissingEmail,
+    /// `MISSING_PASSWORD`: a password is required.
+    MissingPassword,
+    /// `WEAK_PASSWORD`: the password must be 6 characters long or more.
+    WeakPassword,
+    /// `USER_DISABLED`: the user account has been disabled by an administrator.
+    UserDisabled,
+    /// `USER_NOT_FOUND`: the user may have been deleted.
+    UserNotFound,
+    /// `OPERATION_NOT_ALLOWED`: the sign-in method is disabled for this project.
+    OperationNotAllowed,
+    /// `TOO_MANY_ATTEMPTS_TRY_LATER`: requests are blocked due to unusual activity.
+    TooManyAttemptsTryLater,
+    /// `TOKEN_EXPIRED`: the user's credential is no longer valid.
+    TokenExpired,
+    /// `INVALID_ID_TOKEN`: the user's credential is no longer valid.
+    InvalidIdToken,
+    /// `INVALID_REFRESH_TOKEN`: an invalid refresh token was provided.
+    InvalidRefreshToken,
+    /// `CREDENTIAL_TOO_OLD_LOGIN_AGAIN`: the user must sign in again.
+    CredentialTooOldLoginAgain,
+    /// `INVALID_CUSTOM_TOKEN`: the custom token format is incorrect.
+    InvalidCustomToken,
+    /// `CREDENTIAL_MISMATCH`: the custom token corresponds to a different project.
+    CredentialMismatch,
+    /// `INVALID_PROVIDER_ID`: the identity provider ID is invalid.
+    InvalidProviderId,
+    /// `INVALID_OOB_CODE`: the action code is invalid.
+    InvalidOobCode,
+    /// `EXPIRED_OOB_CODE`: the action code has expired.
+    ExpiredOobCode,
+    /// The API key was rejected.
+    InvalidApiKey,
+    /// `Auth` was constructed without an API key.
+    MissingApiKey,
+    /// The method requires a signed-in user.
+    NotSignedIn,
+    /// An Identity Provider was used without configuring a redirect URI.
+    MissingRedirectUri,
+    /// The request never got a response, e.g. the network is down.
+    Network(String),
+    /// The session could not be read from or written to storage.
+    Storage(String),
+    /// An `AuthorizedClient` request got a non-success status.
+    Http { status: u16, body: String },
+    /// The response did not have the expected shape.
+    InvalidResponse(String),
+    /// Any other error, holding the original message.
+    Unknown(String),
+}
+
+impl AuthError {
+    /// Maps an error message thrown by `main.js` to an `AuthError`.
+    ///
+    /// Messages that have an explanation appended to the code
+    /// (`WEAK_PASSWORD : Password should be at least 6 characters`) are
+    /// matched on the code alone.
+    pub fn from_message(message: &str) -> Self {
+        let code = message.split(" : ").next().unwrap_or(message).trim();
+
+        match code {
+            "EMAIL_EXISTS" => AuthError::EmailExists,
+            "EMAIL_NOT_FOUND" => AuthError::EmailNotFound,
+            "INVALID_PASSWORD" => AuthError::InvalidPassword,
+            "INVALID_LOGIN_CREDENTIALS" => AuthError::InvalidLoginCredentials,
+            "INVALID_EMAIL" => AuthError::InvalidEmail,
+            "MISSING_EMAIL" => AuthError::MissingEmail,
+            "MISSING_PASSWORD" => AuthError::MissingPassword,
+            "WEAK_PASSWORD" => AuthError::WeakPassword,
+            "USER_DISABLED" => AuthError::UserDisabled,
+            "USER_NOT_FOUND" => AuthError::UserNotFound,
+            "OPERATION_NOT_ALLOWED" => AuthError::OperationNotAllowed,
+            "TOO_MANY_ATTEMPTS_TRY_LATER" => AuthError::TooManyAttemptsTryLater,
+            "TOKEN_EXPIRED" => AuthError::TokenExpired,
+            "INVALID_ID_TOKEN" => AuthError::InvalidIdToken,
+            "INVALID_REFRESH_TOKEN" => AuthError::InvalidRefreshToken,
+            "CREDENTIAL_TOO_OLD_LOGIN_AGAIN" => AuthError::CredentialTooOldLoginAgain,
+            "INVALID_CUSTOM_TOKEN" => AuthError::InvalidCustomToken,
+            "CREDENTIAL_MISMATCH" => AuthError::CredentialMismatch,
+            "INVALID_PROVIDER_ID" => AuthError::InvalidProviderId,
+            "INVALID_OOB_CODE" => AuthError::InvalidOobCode,
+            "EXPIRED_OOB_CODE" => AuthError::ExpiredOobCode,
+            NOT_SIGNED_IN | LINK_ACCOUNT_SIGNED_OUT => AuthError::NotSignedIn,
+            MISSING_REDIRECT_URI => AuthError::MissingRedirectUri,
+            MISSING_API_KEY => AuthError::MissingApiKey,
+            c if c.starts_with(INVALID_API_KEY) => AuthError::InvalidApiKey,
+            _ => AuthError::Unknown(message.to_string()),
+        }
+    }
+
+    /// The identitytoolkit error code for this error, if it has one.
+    pub fn code(&self) -> Option<&'static str> {
+        let code = match self {
+            AuthError::EmailExists => "EMAIL_EXISTS",
+            AuthError::EmailNotFound => "EMAIL_NOT_FOUND",
+            AuthError::InvalidPassword => "INVALID_PASSWORD",
+            AuthError::InvalidLoginCredentials => "INVALID_LOGIN_CREDENTIALS",
+            AuthError::InvalidEmail => "INVALID_EMAIL",
+            AuthError::MissingEmail => "MISSING_EMAIL",
+            AuthError::MissingPassword => "MISSING_PASSWORD",
+            AuthError::WeakPassword => "WEAK_PASSWORD",
+            AuthError::UserDisabled => "USER_DISABLED",
+            AuthError::UserNotFound => "USER_NOT_FOUND",
+            AuthError::OperationNotAllowed => "OPERATION_NOT_ALLOWED",
+            AuthError::TooManyAttemptsTryLater => "TOO_MANY_ATTEMPTS_TRY_LATER",
+            AuthError::TokenExpired => "TOKEN_EXPIRED",
+            AuthError::InvalidIdToken => "INVALID_ID_TOKEN",
+            AuthError::InvalidRefreshToken => "INVALID_REFRESH_TOKEN",
+            AuthError::CredentialTooOldLoginAgain => "CREDENTIAL_TOO_OLD_LOGIN_AGAIN",
+            AuthError::InvalidCustomToken => "INVALID_CUSTOM_TOKEN",
+            AuthError::CredentialMismatch => "CREDENTIAL_MISMATCH",
+            AuthError::InvalidProviderId => "INVALID_PROVIDER_ID",
+            AuthError::InvalidOobCode => "INVALID_OOB_CODE",
+            AuthError::ExpiredOobCode => "EXPIRED_OOB_CODE",
+            _ => return None,
+        };
+        Some(code)
+    }
+}
+
+impl fmt::Display for AuthError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            AuthError::EmailExists => write!(f, "The email address is already in use"),
+            AuthError::EmailNotFound => write!(f, "There is no user with this email address"),
+            AuthError::InvalidPassword => write!(f, "The password is invalid"),
+            AuthError::InvalidLoginCredentials => write!(f, "The email or password is invalid"),
+            AuthError::InvalidEmail => write!(f, "The email address is badly formatted"),
+            AuthError::MissingEmail => write!(f, "An email address is required"),
+            AuthError::MissingPassword => write!(f, "A password is required"),
+            AuthError::WeakPassword => write!(f, "The password must be 6 characters or more"),
+            AuthError::UserDisabled => write!(f, "The user has been disabled by an administrator"),
+            AuthError::UserNotFound => write!(f, "The user was not found"),
+            AuthError::OperationNotAllowed => {
+                write!(f, "This sign-in method is disabled for the project")
+            }
+            AuthError::TooManyAttemptsTryLater => {
+                write!(f, "Too many attempts, try again later")
+            }
+            AuthError::TokenExpired => write!(f, "The user's credential has expired"),
+            AuthError::InvalidIdToken => write!(f, "The user's credential is no longer valid"),
+            AuthError::InvalidRefreshToken => write!(f, "The refresh token is invalid"),
+            AuthError::CredentialTooOldLoginAgain => {
+                write!(f, "The user must sign in again to perform this operation")
+            }
+            AuthError::InvalidCustomToken => write!(f, "The custom token is invalid"),
+            AuthError::CredentialMismatch => {
+                write!(f, "The custom token is for a different project")
+            }
+            AuthError::InvalidProviderId => write!(f, "The identity provider ID is invalid"),
+            AuthError::InvalidOobCode => write!(f, "The action code is invalid"),
+            AuthError::ExpiredOobCode => write!(f, "The action code has expired"),
+            AuthError::InvalidApiKey => write!(f, "The API key is not valid"),
+            AuthError::MissingApiKey => write!(f, "An API key is required"),
+            AuthError::NotSignedIn => write!(f, "The user must be signed-in to use this method"),
+            AuthError::MissingRedirectUri => {
+                write!(f, "A redirect URI is required to use an Identity Provider")
+            }
+            AuthError::Network(message) => write!(f, "Network error: {}", message),
+            AuthError::Storage(message) => write!(f, "Storage error: {}", message),
+            AuthError::Http { status, body } => write!(f, "HTTP {}: {}", status, body),
+            AuthError::InvalidResponse(message) => write!(f, "Invalid response: {}", message),
+            AuthError::Unknown(message) => write!(f, "{}", message),
+        }
+    }
+}
+
+impl std::error::Error for AuthError {}
+
+impl From<JsValue> for AuthError {
+    fn from(value: JsValue) -> Self {
+        // `fetch` rejects with a TypeError when the request could not be made,
+        // any other TypeError is a bug and falls through to `Unknown`.
+        if let Some(error) = value.dyn_ref::<js_sys::TypeError>() {
+            let message = String::from(error.message());
+            if FETCH_FAILED.iter().any(|m| message.starts_with(m)) {
+                return AuthError::Network(message);
+            }
+            return AuthError::Unknown(message);
+        }
+
+        match value.dyn_ref::<js_sys::Error>() {
+            Some(error) => AuthError::from_message(&String::from(error.message())),
+            None => AuthError::Unknown(value.as_string().unwrap_or_else(|| format!("{:?}", value))),
+        }
+    }
+}
@@ -1,87 +1,372 @@
-use serde::Deserialize;
+use serde::{de::DeserializeOwned, Deserialize, Serialize};
+use std::{cell::RefCell, collections::HashMap, rc::Rc};
 use wasm_bindgen::prelude::*;
 
+mod action_code;
+mod auth_client;
+mod client;
+#[cfg(feature = "yew")]
+pub mod components;
+mod email_action;
+mod error;
+#[cfg(feature = "native")]
+pub mod native;
+pub mod provider;
+mod refresh;
+mod state;
+mod storage;
+#[cfg(feature = "testing")]
+pub mod testing;
+mod token;
+mod user;
+#[cfg(feature = "verify")]
+pub mod verify;
+
+pub use action_code::{ActionCodeInfo, ActionCodeOperation, ActionCodeSettings, OobRequest};
+pub use auth_client::{AuthClient, AuthStreams};
+pub use client::AuthorizedClient;
+pub use email_action::{EmailAction, EmailActionMode, EmailActionOutcome};
+pub use error::AuthError;
+pub use provider::{OauthFlowOptions, Prompt, Provider};
+pub use refresh::TokenRefresher;
+pub use state::{AuthStateListener, AuthStateStream, IdTokenStream};
+pub use storage::{
+    AuthStorage, IndexedDbStorage, LocalStorage, MemoryStorage, Persistence, SessionStorage,
+};
+pub use token::{FirebaseClaims, IdToken, IdTokenClaims};
+pub use user::{
+    MfaInfo, ProfileAttribute, ProfileUpdate, ProviderUserInfo, TokenManager, UserProfile,
+};
+
 #[wasm_bindgen(module = "/main.js")]
 extern "C" {
+    #[derive(Clone, PartialEq)]
     pub type Auth;
 
     #[wasm_bindgen(constructor)]
-    pub fn new(opts: AuthOptions) -> Auth;
+    fn js_new(opts: AuthOptions) -> Auth;
+
+    /// The name this instance was created with, `"default"` unless set with `AuthOptions::with_name`.
+    #[wasm_bindgen(method, getter)]
+    pub fn name(this: &Auth) -> String;
 
     #[wasm_bindgen(method, js_name = "signOut", catch)]
-    pub async fn sign_out(this: &Auth) -> Result<(), JsValue>;
+    async fn js_sign_out(this: &Auth) -> Result<(), JsValue>;
 
     #[wasm_bindgen(method, js_name = "signInWithCustomToken", catch)]
-    pub async fn sign_in_with_custom_token(this: &Auth, token: String) -> Result<JsValue, JsValue>;
+    async fn js_sign_in_with_custom_token(this: &Auth, token: String) -> Result<JsValue, JsValue>;
 
     #[wasm_bindgen(method, js_name = "signInWithProvider", catch)]
-    pub async fn sign_in_with_provider(
-        this: &Auth,
-        options: OauthFlowOptions,
-    ) -> Result<(), JsValue>;
+    async fn js_sign_in_with_provider(this: &Auth, options: JsValue) -> Result<(), JsValue>;
 
     #[wasm_bindgen(method, js_name = "handleSignInRedirect", catch)]
-    pub async fn handle_sign_in_redirect(this: &Auth) -> Result<(), JsValue>;
+    async fn js_handle_sign_in_redirect(this: &Auth) -> Result<(), JsValue>;
 
     #[wasm_bindgen(method, js_name = "signUp", catch)]
-    pub async fn signUp(this: &Auth, email: String, password: String) -> Result<JsValue, JsValue>;
+    async fn js_sign_up(this: &Auth, email: String, password: String) -> Result<JsValue, JsValue>;
+
+    // `signUp` without arguments creates an anonymous account.
+    #[wasm_bindgen(method, js_name = "signUp", catch)]
+    async fn js_sign_up_anonymously(this: &Auth) -> Result<JsValue, JsValue>;
 
     #[wasm_bindgen(method, js_name = "signIn", catch)]
-    pub async fn sign_in(this: &Auth, email: String, password: String) -> Result<JsValue, JsValue>;
+    async fn js_sign_in(this: &Auth, email: String, password: String) -> Result<JsValue, JsValue>;
 
     #[wasm_bindgen(method, js_name = "sendOobCode", catch)]
-    pub async fn send_oob_code(
+    async fn js_send_oob_code(
         this: &Auth,
         request_type: String,
         email: String,
+        action_code_settings: JsValue,
     ) -> Result<(), JsValue>;
 
+    #[wasm_bindgen(method, js_name = "sendSignInLink", catch)]
+    async fn js_send_sign_in_link(
+        this: &Auth,
+        email: String,
+        action_code_settings: JsValue,
+    ) -> Result<(), JsValue>;
+
+    #[wasm_bindgen(method, js_name = "signInWithEmailLink", catch)]
+    async fn js_sign_in_with_email_link(
+        this: &Auth,
+        oob_code: String,
+        email: Option<String>,
+    ) -> Result<JsValue, JsValue>;
+
     #[wasm_bindgen(method, js_name = "resetPassword", catch)]
-    pub async fn reset_password(
+    async fn js_reset_password(
         this: &Auth,
         oob_code: String,
         new_password: String,
     ) -> Result<JsValue, JsValue>;
 
+    #[wasm_bindgen(method, js_name = "checkActionCode", catch)]
+    async fn js_check_action_code(this: &Auth, oob_code: String) -> Result<JsValue, JsValue>;
+
+    #[wasm_bindgen(method, js_name = "applyActionCode", catch)]
+    async fn js_apply_action_code(this: &Auth, oob_code: String) -> Result<(), JsValue>;
+
     #[wasm_bindgen(method, js_name = "fetchProvidersForEmail", catch)]
-    pub async fn fetch_providers_for_email(this: &Auth, email: String) -> Result<JsValue, JsValue>;
+    async fn js_fetch_providers_for_email(this: &Auth, email: String) -> Result<JsValue, JsValue>;
 
     #[wasm_bindgen(method, js_name = "fetchProfile", catch)]
-    pub async fn fetch_profile(this: &Auth) -> Result<JsValue, JsValue>;
+    async fn js_fetch_profile(this: &Auth) -> Result<JsValue, JsValue>;
 
     #[wasm_bindgen(method, js_name = "updateProfile", catch)]
-    pub async fn update_profile(this: &Auth, new_data: js_sys::Object) -> Result<JsValue, JsValue>;
+    async fn js_update_profile(this: &Auth, new_data: JsValue) -> Result<JsValue, JsValue>;
+
+    #[wasm_bindgen(method, js_name = "linkWithEmailPassword", catch)]
+    async fn js_link_with_email_password(
+        this: &Auth,
+        email: String,
+        password: String,
+    ) -> Result<JsValue, JsValue>;
 
     #[wasm_bindgen(method, js_name = "deleteAccount", catch)]
-    pub async fn delete_account(this: &Auth) -> Result<(), JsValue>;
+    async fn js_delete_account(this: &Auth) -> Result<(), JsValue>;
+
+    #[wasm_bindgen(method, js_name = "authorizedRequest", catch)]
+    async fn js_authorized_request(
+        this: &Auth,
+        request: web_sys::Request,
+    ) -> Result<JsValue, JsValue>;
+
+    #[wasm_bindgen(method, js_name = "enforceAuth", catch)]
+    async fn js_enforce_auth(this: &Auth) -> Result<(), JsValue>;
 
+    #[wasm_bindgen(method, js_name = "refreshIdToken", catch)]
+    async fn js_refresh_id_token(this: &Auth, force_refresh: bool) -> Result<(), JsValue>;
+
+    #[wasm_bindgen(method, js_name = "listen")]
+    fn js_listen(this: &Auth, callback: &Closure<dyn FnMut(JsValue)>) -> js_sys::Function;
+
+    #[wasm_bindgen(method, js_name = "listenIdToken")]
+    fn js_listen_id_token(this: &Auth, callback: &Closure<dyn FnMut(JsValue)>) -> js_sys::Function;
+
+    #[wasm_bindgen(method, getter, js_name = "user")]
+    fn js_user(this: &Auth) -> JsValue;
+}
+
+thread_local! {
+    // Keyed like the storage keys of `main.js`, by API key and name.
+    static INSTANCES: RefCell<HashMap<(String, String), Auth>> = RefCell::new(HashMap::new());
+}
+
+impl Auth {
+    /// Creates an `Auth` from `opts` and registers it under their API key and name,
+    /// replacing the instance registered there before.
+    pub fn new(opts: AuthOptions) -> Auth {
+        let key = (opts.api_key.clone(), opts.name.clone());
+        let auth = Auth::js_new(opts);
+
+        INSTANCES.with(|instances| instances.borrow_mut().insert(key, auth.clone()));
+        auth
+    }
+
+    /// Returns the instance registered under the API key and name of `opts`,
+    /// or creates it with `Auth::new` if there is none. `opts` are ignored
+    /// when the instance already exists.
+    pub fn get_or_init(opts: AuthOptions) -> Auth {
+        match Auth::instance(&opts.api_key, &opts.name) {
+            Some(auth) => auth,
+            None => Auth::new(opts),
+        }
+    }
+
+    /// Returns a handle to the instance registered under `api_key` and `name`,
+    /// sharing its session and listeners.
+    pub fn instance(api_key: &str, name: &str) -> Option<Auth> {
+        INSTANCES.with(|instances| {
+            instances
+                .borrow()
+                .get(&(api_key.to_owned(), name.to_owned()))
+                .cloned()
+        })
+    }
+
+    /// Unregisters the instance under `api_key` and `name`. Existing handles keep working.
+    pub fn remove_instance(api_key: &str, name: &str) -> Option<Auth> {
+        INSTANCES.with(|instances| {
+            instances
+                .borrow_mut()
+                .remove(&(api_key.to_owned(), name.to_owned()))
+        })
+    }
+
+    pub async fn sign_out(&self) -> Result<(), AuthError> {
+        Ok(self.js_sign_out().await?)
+    }
+
+    pub async fn sign_in_with_custom_token(&self, token: String) -> Result<UserProfile, AuthError> {
+        from_js(self.js_sign_in_with_custom_token(token).await?)
+    }
+
+    /// Redirects to the sign-in page of the provider, `handle_sign_in_redirect`
+    /// finishes the sign-in when the page is loaded again.
+    pub async fn sign_in_with_provider(&self, options: OauthFlowOptions) -> Result<(), AuthError> {
+        options.provider().validate()?;
+        Ok(self.js_sign_in_with_provider(to_js(&options)?).await?)
+    }
+
+    pub async fn handle_sign_in_redirect(&self) -> Result<(), AuthError> {
+        Ok(self.js_handle_sign_in_redirect().await?)
+    }
+
+    pub async fn sign_up(&self, email: String, password: String) -> Result<UserProfile, AuthError> {
+        from_js(self.js_sign_up(email, password).await?)
+    }
+
+    pub async fn sign_in(&self, email: String, password: String) -> Result<UserProfile, AuthError> {
+        from_js(self.js_sign_in(email, password).await?)
+    }
+
+    /// Signs in as a new anonymous user, e.g. a guest that registers later.
+    pub async fn sign_in_anonymously(&self) -> Result<UserProfile, AuthError> {
+        from_js(self.js_sign_up_anonymously().await?)
+    }
+
+    /// Sends an out-of-band email, its link leads to the `redirect_uri` with the email
+    /// as a query parameter. The email is ignored for `OobRequest::VerifyEmail`.
+    pub async fn send_oob_code(&self, request: OobRequest, email: String) -> Result<(), AuthError> {
+        Ok(self
+            .js_send_oob_code(request.as_str().into(), email, JsValue::UNDEFINED)
+            .await?)
+    }
+
+    /// Like [`Auth::send_oob_code`], with the link leading where `settings` say.
+    pub async fn send_oob_code_with_settings(
+        &self,
+        request: OobRequest,
+        email: String,
+        settings: &ActionCodeSettings,
+    ) -> Result<(), AuthError> {
+        Ok(self
+            .js_send_oob_code(request.as_str().into(), email, to_js(settings)?)
+            .await?)
+    }
+
+    /// Emails a passwordless sign-in link to `email`, `settings` must handle the code in app.
+    ///
+    /// The email is saved to the configured storage, so [`Auth::complete_email_link_sign_in`]
+    /// doesn't have to ask for it again when the link is opened on the same device.
+    pub async fn send_sign_in_link(
+        &self,
+        email: String,
+        settings: &ActionCodeSettings,
+    ) -> Result<(), AuthError> {
+        Ok(self.js_send_sign_in_link(email, to_js(settings)?).await?)
+    }
+
+    /// Whether `link` is an email sign-in link, e.g. the current location.
+    pub fn is_sign_in_with_email_link(&self, link: &str) -> bool {
+        action_code::sign_in_oob_code(link).is_some()
+    }
+
+    /// Signs in with the email sign-in `link`. Without an `email`, the one saved by
+    /// [`Auth::send_sign_in_link`] is used, and it fails with `MissingEmail` if there is none,
+    /// e.g. because the link was opened on another device.
+    pub async fn complete_email_link_sign_in(
+        &self,
+        link: &str,
+        email: Option<String>,
+    ) -> Result<UserProfile, AuthError> {
+        let oob_code = action_code::sign_in_oob_code(link).ok_or(AuthError::InvalidOobCode)?;
+        from_js(self.js_sign_in_with_email_link(oob_code, email).await?)
+    }
+
+    /// Resets the password and returns the email of the account the code was issued for.
+    pub async fn reset_password(
+        &self,
+        oob_code: String,
+        new_password: String,
+    ) -> Result<String, AuthError> {
+        from_js(self.js_reset_password(oob_code, new_password).await?)
+    }
+
+    /// What `oob_code` does and who it was sent to, without using it up.
+    pub async fn check_action_code(&self, oob_code: String) -> Result<ActionCodeInfo, AuthError> {
+        from_js(self.js_check_action_code(oob_code).await?)
+    }
+
+    /// Applies a `VerifyEmail`, `VerifyAndChangeEmail` or `RecoverEmail` code.
+    pub async fn apply_action_code(&self, oob_code: String) -> Result<(), AuthError> {
+        Ok(self.js_apply_action_code(oob_code).await?)
+    }
+
+    /// Checks that `oob_code` resets a password, and returns the email of the account.
+    pub async fn verify_password_reset_code(&self, oob_code: String) -> Result<String, AuthError> {
+        action_code::password_reset_email(self.check_action_code(oob_code).await?)
+    }
+
+    pub async fn fetch_providers_for_email(
+        &self,
+        email: String,
+    ) -> Result<ProvidersForEmail, AuthError> {
+        from_js(self.js_fetch_providers_for_email(email).await?)
+    }
+
+    pub async fn fetch_profile(&self) -> Result<UserProfile, AuthError> {
+        from_js(self.js_fetch_profile().await?)
+    }
+
+    /// Applies `update` to the signed-in user and returns the updated profile.
+    pub async fn update_profile(&self, update: ProfileUpdate) -> Result<UserProfile, AuthError> {
+        from_js(self.js_update_profile(to_js(&update)?).await?)
+    }
+
+    /// Links an email and password to the signed-in user, e.g. to turn an
+    /// anonymous user into a permanent account with the same uid.
+    pub async fn link_with_email_password(
+        &self,
+        email: String,
+        password: String,
+    ) -> Result<UserProfile, AuthError> {
+        from_js(self.js_link_with_email_password(email, password).await?)
+    }
+
+    pub async fn delete_account(&self) -> Result<(), AuthError> {
+        Ok(self.js_delete_account().await?)
+    }
+}
+
+fn from_js<T: DeserializeOwned>(value: JsValue) -> Result<T, AuthError> {
+    serde_wasm_bindgen::from_value(value).map_err(|e| AuthError::InvalidResponse(e.to_string()))
+}
+
+/// Serializes `value` to a plain JS object, e.g. a request body for `main.js`.
+fn to_js<T: Serialize>(value: &T) -> Result<JsValue, AuthError> {
+    let serializer = serde_wasm_bindgen::Serializer::json_compatible();
+    value
+        .serialize(&serializer)
+        .map_err(|e| AuthError::Unknown(e.to_string()))
 }
 
 #[derive(Debug, Deserialize)]
-pub struct UserProfile {
-    #[serde(rename = "localId")]
-    pub local_id: String,
-    pub email: String,
-    #[serde(rename = "passwordHash")]
-    pub password_hash: String,
-    #[serde(rename = "emailVerified")]
-    pub email_verified: bool,
-    #[serde(rename = "passwordUpdatedAt")]
-    pub password_updated_at: u64,
-    #[serde(rename = "validSince")]
-    pub valid_since: String,
-    #[serde(rename = "lastLoginAt")]
-    pub last_login_at: String,
-    #[serde(rename = "createdAt")]
-    pub created_at: String,
-    #[serde(rename = "lastRefreshAt")]
-    pub last_refresh_at: String,
+pub struct ProvidersForEmail {
+    #[serde(rename = "allProviders", default)]
+    pub all_providers: Vec<String>,
+    pub registered: bool,
+    #[serde(rename = "sessionId")]
+    pub session_id: String,
+    #[serde(rename = "signinMethods", default)]
+    pub signin_methods: Vec<String>,
 }
 
+const IDENTITY_TOOLKIT_URL: &str = "https://identitytoolkit.googleapis.com";
+const SECURE_TOKEN_URL: &str = "https://securetoken.googleapis.com";
+
+/// The options of `Auth::new`.
+///
+/// `native::Auth` only uses the API key, the redirect URI and the endpoints.
 #[wasm_bindgen]
 pub struct AuthOptions {
     api_key: String,
     redirect_uri: Option<String>,
+    name: String,
+    storage: Option<Rc<dyn AuthStorage>>,
+    identity_toolkit_url: String,
+    secure_token_url: String,
 }
 
 #[wasm_bindgen]
@@ -95,6 +380,36 @@ impl AuthOptions {
     pub fn redirect_uri(&self) -> Option<String> {
         self.redirect_uri.clone()
     }
+
+    #[wasm_bindgen(getter)]
+    pub fn name(&self) -> String {
+        self.name.clone()
+    }
+
+    #[wasm_bindgen(getter)]
+    pub fn storage(&self) -> JsValue {
+        match &self.storage {
+            Some(storage) => storage::storage_adapter(storage.clone()),
+            None => JsValue::UNDEFINED,
+        }
+    }
+
+    #[wasm_bindgen(getter, js_name = "identityToolkitUrl")]
+    pub fn identity_toolkit_url(&self) -> String {
+        self.identity_toolkit_url.clone()
+    }
+
+    #[wasm_bindgen(getter, js_name = "secureTokenUrl")]
+    pub fn secure_token_url(&self) -> String {
+        self.secure_token_url.clone()
+    }
+
+    #[wasm_bindgen(getter, js_name = "syncTabs")]
+    pub fn sync_tabs(&self) -> bool {
+        self.storage
+            .as_ref()
+            .is_none_or(|storage| storage.syncs_across_tabs())
+    }
 }
 
 impl AuthOptions {
@@ -102,6 +417,10 @@ impl AuthOptions {
         AuthOptions {
             api_key: api_key.into(),
             redirect_uri: None,
+            name: "default".into(),
+            storage: None,
+            identity_toolkit_url: IDENTITY_TOOLKIT_URL.into(),
+            secure_token_url: SECURE_TOKEN_URL.into(),
         }
     }
 
@@ -109,64 +428,53 @@ impl AuthOptions {
         self.redirect_uri = Some(redirect_uri);
         self
     }
-}
-
-#[wasm_bindgen]
-pub struct ProviderOptions {
-    name: String,
-    scope: Option<String>,
-}
 
-#[wasm_bindgen]
-impl ProviderOptions {
-    pub fn new(name: String) -> Self {
-        ProviderOptions { name, scope: None }
+    /// Names the instance, so several of them can keep separate sessions
+    /// and be looked up with `Auth::instance`. Browser only, `native::Auth` ignores it.
+    pub fn with_name(mut self, name: &str) -> Self {
+        self.name = name.into();
+        self
     }
 
-    pub fn with_scope(mut self, scope: String) -> Self {
-        self.scope = Some(scope);
+    /// Sends the identitytoolkit and securetoken requests to other base URLs,
+    /// e.g. a backend proxy. The `/v1/...` paths are appended to them.
+    pub fn with_endpoints(mut self, identity_toolkit_url: &str, secure_token_url: &str) -> Self {
+        self.identity_toolkit_url = identity_toolkit_url.trim_end_matches('/').into();
+        self.secure_token_url = secure_token_url.trim_end_matches('/').into();
         self
     }
 
-    pub fn set_scope(&mut self, scope: String) {
-        self.scope = Some(scope);
-    }
+    /// Uses the Firebase Auth Emulator running at `host`, e.g. `localhost:9099`.
+    pub fn with_emulator(self, host: &str) -> Self {
+        let host = host.trim_end_matches('/');
+        let base = if host.starts_with("http://") || host.starts_with("https://") {
+            host.to_string()
+        } else {
+            format!("http://{}", host)
+        };
 
-    #[wasm_bindgen(getter)]
-    pub fn name(&self) -> String {
-        self.name.clone()
+        self.with_endpoints(
+            &format!("{}/identitytoolkit.googleapis.com", base),
+            &format!("{}/securetoken.googleapis.com", base),
+        )
     }
 
-    #[wasm_bindgen(getter)]
-    pub fn scope(&self) -> Option<String> {
-        self.scope.clone()
+    /// Persists the session in `storage` instead of `localStorage`.
+    ///
+    /// Browser only, `native::Auth` keeps the session in memory and ignores it.
+    pub fn with_storage<S: AuthStorage + 'static>(mut self, storage: S) -> Self {
+        self.storage = Some(Rc::new(storage));
+        self
     }
-}
-
-#[wasm_bindgen]
-pub struct OauthFlowOptions {
-    provider: String,
-    context: String,
-    #[wasm_bindgen(js_name = "linkAccount")]
-    pub link_account: bool,
-}
 
-#[wasm_bindgen]
-impl OauthFlowOptions {
-    pub fn new(provider: String, context: String, link_account: bool) -> Self {
-        OauthFlowOptions {
-            provider,
-            context,
-            link_account,
+    /// Chooses whether the session survives reloads, lasts for the tab or only lives in memory.
+    ///
+    /// Browser only, like `with_storage`.
+    pub fn with_persistence(self, persistence: Persistence) -> Self {
+        match persistence {
+            Persistence::Local => self.with_storage(LocalStorage),
+            Persistence::Session => self.with_storage(SessionStorage),
+            Persistence::None => self.with_storage(MemoryStorage::new()),
         }
     }
-
-    #[wasm_bindgen(getter)]
-    pub fn provider(&self) -> String {
-        self.provider.clone()
-    }
-    #[wasm_bindgen(getter)]
-    pub fn context(&self) -> String {
-        self.context.clone()
-    }
 }
@@ -0,0 +1,616 @@
+//! A pure-Rust `Auth` that talks to the identitytoolkit REST API directly,
+//! for use outside the browser, e.g. CLI tools and integration tests.
+//!
+//! It mirrors the logic of `main.js`, but keeps the session in memory.
+
+use crate::{
+    action_code, email_action, ActionCodeInfo, ActionCodeSettings, AuthClient, AuthError,
+    AuthOptions, EmailAction, EmailActionOutcome, IdToken, OobRequest, ProfileUpdate, TokenManager,
+    UserProfile,
+};
+use async_trait::async_trait;
+use chrono::{Duration, Utc};
+use futures::lock::Mutex as AsyncMutex;
+use serde::{de::DeserializeOwned, Deserialize, Serialize};
+use serde_json::json;
+use std::sync::{Arc, Mutex};
+
+/// Encapsulates the authentication flow logic, like the browser `Auth`.
+///
+/// Clones share the same session.
+#[derive(Clone)]
+pub struct Auth {
+    inner: Arc<Inner>,
+}
+
+struct Inner {
+    http: reqwest::Client,
+    api_key: String,
+    redirect_uri: Option<String>,
+    identity_toolkit_url: String,
+    secure_token_url: String,
+    user: Mutex<Option<UserProfile>>,
+    /// The email of the last `send_sign_in_link`, like `EmailForSignIn` in `main.js`.
+    email_for_sign_in: Mutex<Option<String>>,
+    // Held while refreshing, so concurrent calls don't make more than one request.
+    refresh: AsyncMutex<()>,
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct SignInResponse {
+    id_token: String,
+    refresh_token: String,
+    expires_in: Option<String>,
+}
+
+#[derive(Deserialize)]
+struct RefreshResponse {
+    id_token: String,
+    refresh_token: String,
+    expires_in: Option<String>,
+}
+
+#[derive(Deserialize)]
+struct LookupResponse {
+    #[serde(default)]
+    users: Vec<UserProfile>,
+}
+
+#[derive(Deserialize)]
+struct ErrorResponse {
+    error: ErrorBody,
+}
+
+#[derive(Deserialize)]
+struct ErrorBody {
+    message: String,
+}
+
+impl Auth {
+    /// The session is kept in memory, so the browser-only options of `opts` are
+    /// ignored: `with_name`, `with_storage` and `with_persistence`.
+    pub fn new(opts: AuthOptions) -> Self {
+        Auth::with_client(opts, reqwest::Client::new())
+    }
+
+    /// Uses `http` to make the requests, e.g. to configure proxies or timeouts.
+    pub fn with_client(opts: AuthOptions, http: reqwest::Client) -> Self {
+        Auth {
+            inner: Arc::new(Inner {
+                http,
+                api_key: opts.api_key(),
+                redirect_uri: opts.redirect_uri(),
+                identity_toolkit_url: opts.identity_toolkit_url(),
+                secure_token_url: opts.secure_token_url(),
+                user: Mutex::new(None),
+                email_for_sign_in: Mutex::new(None),
+                refresh: AsyncMutex::new(()),
+            }),
+        }
+    }
+
+    /// The signed-in user, without making a request.
+    ///
+    /// The session is in memory so this doesn't fail, it returns a `Result` like the browser `Auth`.
+    pub fn current_user(&self) -> Result<Option<UserProfile>, AuthError> {
+        Ok(self.user())
+    }
+
+    pub async fn sign_out(&self) -> Result<(), AuthError> {
+        self.set_user(None);
+        Ok(())
+    }
+
+    pub async fn sign_in_with_custom_token(&self, token: String) -> Result<UserProfile, AuthError> {
+        let response: SignInResponse = self
+            .api(
+                "signInWithCustomToken",
+                &json!({ "token": token, "returnSecureToken": true }),
+            )
+            .await?;
+        self.fetch_profile_with(response.into()).await
+    }
+
+    pub async fn sign_up(&self, email: String, password: String) -> Result<UserProfile, AuthError> {
+        let response: SignInResponse = self
+            .api(
+                "signUp",
+                &json!({ "email": email, "password": password, "returnSecureToken": true }),
+            )
+            .await?;
+        self.fetch_profile_with(response.into()).await
+    }
+
+    pub async fn sign_in(&self, email: String, password: String) -> Result<UserProfile, AuthError> {
+        let response: SignInResponse = self
+            .api(
+                "signInWithPassword",
+                &json!({ "email": email, "password": password, "returnSecureToken": true }),
+            )
+            .await?;
+        self.fetch_profile_with(response.into()).await
+    }
+
+    /// Signs in as a new anonymous user, e.g. a guest that registers later.
+    pub async fn sign_in_anonymously(&self) -> Result<UserProfile, AuthError> {
+        let response: SignInResponse = self
+            .api("signUp", &json!({ "returnSecureToken": true }))
+            .await?;
+        self.fetch_profile_with(response.into()).await
+    }
+
+    /// Sends an out-of-band email, its link leads to the `redirect_uri` with the email
+    /// as a query parameter. The email is ignored for `OobRequest::VerifyEmail`.
+    pub async fn send_oob_code(&self, request: OobRequest, email: String) -> Result<(), AuthError> {
+        self.send_oob(request, email, None).await
+    }
+
+    /// Like [`Auth::send_oob_code`], with the link leading where `settings` say.
+    pub async fn send_oob_code_with_settings(
+        &self,
+        request: OobRequest,
+        email: String,
+        settings: &ActionCodeSettings,
+    ) -> Result<(), AuthError> {
+        self.send_oob(request, email, Some(settings)).await
+    }
+
+    async fn send_oob(
+        &self,
+        request: OobRequest,
+        email: String,
+        settings: Option<&ActionCodeSettings>,
+    ) -> Result<(), AuthError> {
+        let mut body = match settings {
+            Some(settings) => json_object(settings)?,
+            None => serde_json::Map::new(),
+        };
+        body.insert("requestType".into(), request.as_str().into());
+
+        // These are sent on behalf of the signed-in user.
+        let email = match request {
+            OobRequest::VerifyEmail | OobRequest::VerifyAndChangeEmail => {
+                let user = self.enforce_auth().await?;
+                body.insert("idToken".into(), id_token(&user)?.into());
+                match request {
+                    OobRequest::VerifyEmail => user.email.unwrap_or_default(),
+                    _ => email,
+                }
+            }
+            _ => email,
+        };
+
+        if settings.is_none() {
+            if let Some(redirect_uri) = &self.inner.redirect_uri {
+                let continue_url = format!("{}?email={}", redirect_uri, email);
+                body.insert("continueUrl".into(), continue_url.into());
+            }
+        }
+        let email_field = match request {
+            OobRequest::VerifyAndChangeEmail => "newEmail",
+            _ => "email",
+        };
+        body.insert(email_field.into(), email.into());
+
+        self.api::<serde_json::Value, _>("sendOobCode", &body)
+            .await
+            .map(|_| ())
+    }
+
+    /// Emails a passwordless sign-in link to `email`, `settings` must handle the code in app.
+    ///
+    /// The email is remembered, so [`Auth::complete_email_link_sign_in`] doesn't have to ask for it.
+    pub async fn send_sign_in_link(
+        &self,
+        email: String,
+        settings: &ActionCodeSettings,
+    ) -> Result<(), AuthError> {
+        self.send_oob(OobRequest::EmailSignIn, email.clone(), Some(settings))
+            .await?;
+        *self.inner.email_for_sign_in.lock().unwrap() = Some(email);
+        Ok(())
+    }
+
+    /// Whether `link` is an email sign-in link.
+    pub fn is_sign_in_with_email_link(&self, link: &str) -> bool {
+        action_code::sign_in_oob_code(link).is_some()
+    }
+
+    /// Signs in with the email sign-in `link`. Without an `email`, the one remembered by
+    /// [`Auth::send_sign_in_link`] is used, and it fails with `MissingEmail` if there is none.
+    pub async fn complete_email_link_sign_in(
+        &self,
+        link: &str,
+        email: Option<String>,
+    ) -> Result<UserProfile, AuthError> {
+        let oob_code = action_code::sign_in_oob_code(link).ok_or(AuthError::InvalidOobCode)?;
+        self.sign_in_with_email_link(oob_code, email).await
+    }
+
+    async fn sign_in_with_email_link(
+        &self,
+        oob_code: String,
+        email: Option<String>,
+    ) -> Result<UserProfile, AuthError> {
+        let email = email
+            .or_else(|| self.inner.email_for_sign_in.lock().unwrap().clone())
+            .ok_or(AuthError::MissingEmail)?;
+
+        let response: SignInResponse = self
+            .api(
+                "signInWithEmailLink",
+                &json!({ "oobCode": oob_code, "email": email, "returnSecureToken": true }),
+            )
+            .await?;
+        let user = self.fetch_profile_with(response.into()).await?;
+
+        *self.inner.email_for_sign_in.lock().unwrap() = None;
+        Ok(user)
+    }
+
+    /// Resets the password and returns the email of the account the code was issued for.
+    pub async fn reset_password(
+        &self,
+        oob_code: String,
+        new_password: String,
+    ) -> Result<String, AuthError> {
+        #[derive(Deserialize)]
+        struct ResetPasswordResponse {
+            email: String,
+        }
+
+        let response: ResetPasswordResponse = self
+            .api(
+                "resetPassword",
+                &json!({ "oobCode": oob_code, "newPassword": new_password }),
+            )
+            .await?;
+        Ok(response.email)
+    }
+
+    /// What `oob_code` does and who it was sent to, without using it up.
+    pub async fn check_action_code(&self, oob_code: String) -> Result<ActionCodeInfo, AuthError> {
+        self.api("resetPassword", &json!({ "oobCode": oob_code }))
+            .await
+    }
+
+    /// Applies a `VerifyEmail`, `VerifyAndChangeEmail` or `RecoverEmail` code.
+    pub async fn apply_action_code(&self, oob_code: String) -> Result<(), AuthError> {
+        self.api::<serde_json::Value, _>("update", &json!({ "oobCode": oob_code }))
+            .await
+            .map(|_| ())
+    }
+
+    /// Checks that `oob_code` resets a password, and returns the email of the account.
+    pub async fn verify_password_reset_code(&self, oob_code: String) -> Result<String, AuthError> {
+        action_code::password_reset_email(self.check_action_code(oob_code).await?)
+    }
+
+    /// Makes the call for `action`, except resetting the password which needs the new one.
+    ///
+    /// Email links are completed with the email remembered by `send_sign_in_link`.
+    pub async fn handle_email_action(
+        &self,
+        action: &EmailAction,
+    ) -> Result<EmailActionOutcome, AuthError> {
+        email_action::handle_email_action(self, action).await
+    }
+
+    /// Gets the user data from the server and updates the session.
+    pub async fn fetch_profile(&self) -> Result<UserProfile, AuthError> {
+        let user = self.enforce_auth().await?;
+        let token_manager = user.token_manager.ok_or(AuthError::NotSignedIn)?;
+        self.fetch_profile_with(token_manager).await
+    }
+
+    /// Applies `update` to the signed-in user and returns the updated profile.
+    pub async fn update_profile(&self, update: ProfileUpdate) -> Result<UserProfile, AuthError> {
+        #[derive(Deserialize)]
+        #[serde(rename_all = "camelCase")]
+        struct UpdateResponse {
+            id_token: Option<String>,
+            refresh_token: Option<String>,
+            expires_in: Option<String>,
+        }
+
+        let user = self.enforce_auth().await?;
+
+        let mut body = json_object(&update)?;
+        body.insert("idToken".into(), id_token(&user)?.into());
+        body.insert("returnSecureToken".into(), true.into());
+
+        let response: serde_json::Value = self.api("update", &body).await?;
+        let tokens: UpdateResponse = serde_json::from_value(response.clone())
+            .map_err(|e| AuthError::InvalidResponse(e.to_string()))?;
+        let mut updated: UserProfile = serde_json::from_value(response)
+            .map_err(|e| AuthError::InvalidResponse(e.to_string()))?;
+
+        updated.token_manager = match (tokens.id_token, tokens.refresh_token) {
+            (Some(id_token), Some(refresh_token)) => Some(TokenManager {
+                id_token,
+                refresh_token,
+                expires_at: Some(expires_at(tokens.expires_in.as_deref())),
+            }),
+            _ => user.token_manager,
+        };
+
+        self.set_user(Some(updated.clone()));
+        Ok(updated)
+    }
+
+    /// Links an email and password to the signed-in user, e.g. to turn an
+    /// anonymous user into a permanent account with the same uid.
+    pub async fn link_with_email_password(
+        &self,
+        email: String,
+        password: String,
+    ) -> Result<UserProfile, AuthError> {
+        self.update_profile(
+            ProfileUpdate::new()
+                .with_email(&email)
+                .with_password(&password),
+        )
+        .await
+    }
+
+    /// The ID token of the signed-in user, refreshed first if it has expired.
+    pub async fn id_token(&self) -> Result<IdToken, AuthError> {
+        self.get_id_token(false).await
+    }
+
+    /// The ID token of the signed-in user, refreshed first if it has expired or `force_refresh`,
+    /// e.g. to pick up custom claims that were just set.
+    pub async fn get_id_token(&self, force_refresh: bool) -> Result<IdToken, AuthError> {
+        if self.user().is_none() {
+            return Err(AuthError::NotSignedIn);
+        }
+        self.refresh_id_token(force_refresh).await?;
+
+        let user = self.user().ok_or(AuthError::NotSignedIn)?;
+        IdToken::decode(&id_token(&user)?)
+    }
+
+    /// Deletes the currently signed-in account then signs out.
+    pub async fn delete_account(&self) -> Result<(), AuthError> {
+        let user = self.enforce_auth().await?;
+        self.api::<serde_json::Value, _>("delete", &json!({ "idToken": id_token(&user)? }))
+            .await?;
+        self.sign_out().await
+    }
+
+    /// Refreshes the idToken with the refresh token, only if it has expired unless `force_refresh`.
+    pub(crate) async fn refresh_id_token(&self, force_refresh: bool) -> Result<(), AuthError> {
+        if !force_refresh && !self.token_expired() {
+            return Ok(());
+        }
+
+        let _guard = self.inner.refresh.lock().await;
+
+        // Another call may have refreshed the token while we were waiting.
+        if !force_refresh && !self.token_expired() {
+            return Ok(());
+        }
+
+        let user = self.user().ok_or(AuthError::NotSignedIn)?;
+        let refresh_token = user
+            .token_manager
+            .as_ref()
+            .map(|token_manager| token_manager.refresh_token.clone())
+            .ok_or(AuthError::NotSignedIn)?;
+
+        let response: RefreshResponse = self
+            .post(
+                format!(
+                    "{}/v1/token?key={}",
+                    self.inner.secure_token_url, self.inner.api_key
+                ),
+                &json!({ "grant_type": "refresh_token", "refresh_token": refresh_token }),
+            )
+            .await?;
+
+        let mut user = self.user().ok_or(AuthError::NotSignedIn)?;
+        user.token_manager = Some(TokenManager {
+            id_token: response.id_token,
+            refresh_token: response.refresh_token,
+            expires_at: Some(expires_at(response.expires_in.as_deref())),
+        });
+        self.set_user(Some(user));
+        Ok(())
+    }
+
+    /// Makes sure the user is signed-in and has up-to-date credentials.
+    async fn enforce_auth(&self) -> Result<UserProfile, AuthError> {
+        if self.user().is_none() {
+            return Err(AuthError::NotSignedIn);
+        }
+        self.refresh_id_token(false).await?;
+        self.user().ok_or(AuthError::NotSignedIn)
+    }
+
+    async fn fetch_profile_with(
+        &self,
+        token_manager: TokenManager,
+    ) -> Result<UserProfile, AuthError> {
+        let response: LookupResponse = self
+            .api("lookup", &json!({ "idToken": token_manager.id_token }))
+            .await?;
+
+        let mut user = response
+            .users
+            .into_iter()
+            .next()
+            .ok_or(AuthError::UserNotFound)?;
+        user.token_manager = Some(token_manager);
+
+        self.set_user(Some(user.clone()));
+        Ok(user)
+    }
+
+    fn token_expired(&self) -> bool {
+        let expires_at = self
+            .user()
+            .and_then(|user| user.token_manager)
+            .and_then(|token_manager| token_manager.expires_at);
+
+        expires_at.is_none_or(|expires_at| Utc::now() >= expires_at)
+    }
+
+    fn user(&self) -> Option<UserProfile> {
+        self.inner.user.lock().unwrap().clone()
+    }
+
+    fn set_user(&self, user: Option<UserProfile>) {
+        *self.inner.user.lock().unwrap() = user;
+    }
+
+    /// Makes a request to an `accounts:<endpoint>` identitytoolkit endpoint.
+    async fn api<T: DeserializeOwned, B: Serialize + ?Sized>(
+        &self,
+        endpoint: &str,
+        body: &B,
+    ) -> Result<T, AuthError> {
+        self.post(
+            format!(
+                "{}/v1/accounts:{}?key={}",
+                self.inner.identity_toolkit_url, endpoint, self.inner.api_key
+            ),
+            body,
+        )
+        .await
+    }
+
+    async fn post<T: DeserializeOwned, B: Serialize + ?Sized>(
+        &self,
+        url: String,
+        body: &B,
+    ) -> Result<T, AuthError> {
+        let response = self
+            .inner
+            .http
+            .post(url)
+            .json(body)
+            .send()
+            .await
+            .map_err(|e| AuthError::Network(e.to_string()))?;
+
+        let status = response.status();
+        let text = response
+            .text()
+            .await
+            .map_err(|e| AuthError::Network(e.to_string()))?;
+
+        if !status.is_success() {
+            return Err(match serde_json::from_str::<ErrorResponse>(&text) {
+                Ok(response) => AuthError::from_message(&response.error.message),
+                Err(_) => AuthError::Http {
+                    status: status.as_u16(),
+                    body: text,
+                },
+            });
+        }
+
+        serde_json::from_str(&text).map_err(|e| AuthError::InvalidResponse(e.to_string()))
+    }
+}
+
+#[async_trait(?Send)]
+impl AuthClient for Auth {
+    async fn sign_in(&self, email: String, password: String) -> Result<UserProfile, AuthError> {
+        Auth::sign_in(self, email, password).await
+    }
+
+    async fn sign_up(&self, email: String, password: String) -> Result<UserProfile, AuthError> {
+        Auth::sign_up(self, email, password).await
+    }
+
+    async fn sign_in_anonymously(&self) -> Result<UserProfile, AuthError> {
+        Auth::sign_in_anonymously(self).await
+    }
+
+    async fn sign_out(&self) -> Result<(), AuthError> {
+        Auth::sign_out(self).await
+    }
+
+    fn current_user(&self) -> Result<Option<UserProfile>, AuthError> {
+        Auth::current_user(self)
+    }
+
+    async fn get_id_token(&self, force_refresh: bool) -> Result<IdToken, AuthError> {
+        Auth::get_id_token(self, force_refresh).await
+    }
+
+    async fn update_profile(&self, update: ProfileUpdate) -> Result<UserProfile, AuthError> {
+        Auth::update_profile(self, update).await
+    }
+
+    async fn link_with_email_password(
+        &self,
+        email: String,
+        password: String,
+    ) -> Result<UserProfile, AuthError> {
+        Auth::link_with_email_password(self, email, password).await
+    }
+
+    async fn delete_account(&self) -> Result<(), AuthError> {
+        Auth::delete_account(self).await
+    }
+}
+
+#[async_trait(?Send)]
+impl email_action::ActionCodes for Auth {
+    async fn check_action_code(&self, oob_code: String) -> Result<ActionCodeInfo, AuthError> {
+        Auth::check_action_code(self, oob_code).await
+    }
+
+    async fn apply_action_code(&self, oob_code: String) -> Result<(), AuthError> {
+        Auth::apply_action_code(self, oob_code).await
+    }
+
+    async fn verify_password_reset_code(&self, oob_code: String) -> Result<String, AuthError> {
+        Auth::verify_password_reset_code(self, oob_code).await
+    }
+
+    async fn sign_in_with_email_link(&self, oob_code: String) -> Result<UserProfile, AuthError> {
+        Auth::sign_in_with_email_link(self, oob_code, None).await
+    }
+}
+
+impl From<SignInResponse> for TokenManager {
+    fn from(response: SignInResponse) -> Self {
+        TokenManager {
+            id_token: response.id_token,
+            refresh_token: response.refresh_token,
+            expires_at: Some(expires_at(response.expires_in.as_deref())),
+        }
+    }
+}
+
+/// The ID tokens are valid for an hour unless the response says otherwise.
+fn expires_at(expires_in: Option<&str>) -> chrono::DateTime<Utc> {
+    let seconds = expires_in
+        .and_then(|expires_in| expires_in.parse().ok())
+        .unwrap_or(3600);
+    Utc::now() + Duration::seconds(seconds)
+}
+
+/// The fields of `value` as a request body, for adding more fields to it.
+fn json_object<T: Serialize>(
+    value: &T,
+) -> Result<serde_json::Map<String, serde_json::Value>, AuthError> {
+    match serde_json::to_value(value) {
+        Ok(serde_json::Value::Object(map)) => Ok(map),
+        Ok(other) => Err(AuthError::Unknown(format!(
+            "expected a JSON object, got {}",
+            other
+        ))),
+        Err(e) => Err(AuthError::Unknown(e.to_string())),
+    }
+}
+
+fn id_token(user: &UserProfile) -> Result<String, AuthError> {
+    user.token_manager
+        .as_ref()
+        .map(|token_manager| token_manager.id_token.clone())
+        .ok_or(AuthError::NotSignedIn)
+}
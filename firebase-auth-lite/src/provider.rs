@@ -0,0 +1,385 @@
+//! The federated identity providers of `sign_in_with_provider`.
+//!
+//! [`OauthFlowOptions`] takes any [`Provider`]. [`OauthFlowBuilder`] builds the
+//! same options from a type per provider, so options the provider doesn't
+//! support don't compile, e.g. `hd` for anything but Google:
+//!
+//! ```compile_fail
+//! use firebase_auth_lite::provider::{GitHub, OauthFlowBuilder};
+//!
+//! let options = OauthFlowBuilder::new(GitHub).with_hosted_domain("example.com");
+//! ```
+//!
+//! Or OAuth scopes for SAML:
+//!
+//! ```compile_fail
+//! use firebase_auth_lite::provider::{OauthFlowBuilder, Saml};
+//!
+//! let options = OauthFlowBuilder::new(Saml::new("example").unwrap()).with_scope("email");
+//! ```
+
+use crate::AuthError;
+use serde::{Serialize, Serializer};
+use std::marker::PhantomData;
+
+/// A federated identity provider enabled in the project.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Provider {
+    Google,
+    GitHub,
+    Facebook,
+    Microsoft,
+    Apple,
+    Twitter,
+    Yahoo,
+    /// An OpenID Connect provider, by the name after `oidc.` in its provider ID.
+    Oidc(String),
+    /// A SAML provider, by the name after `saml.` in its provider ID.
+    Saml(String),
+}
+
+impl Provider {
+    /// An OpenID Connect provider, `name` is its provider ID with or without the `oidc.` prefix.
+    pub fn oidc(name: &str) -> Result<Provider, AuthError> {
+        unprefixed("oidc.", name).map(Provider::Oidc)
+    }
+
+    /// A SAML provider, `name` is its provider ID with or without the `saml.` prefix.
+    pub fn saml(name: &str) -> Result<Provider, AuthError> {
+        unprefixed("saml.", name).map(Provider::Saml)
+    }
+
+    /// The `providerId` of the provider, e.g. `google.com` or `oidc.example`.
+    pub fn provider_id(&self) -> String {
+        match self {
+            Provider::Google => "google.com".into(),
+            Provider::GitHub => "github.com".into(),
+            Provider::Facebook => "facebook.com".into(),
+            Provider::Microsoft => "microsoft.com".into(),
+            Provider::Apple => "apple.com".into(),
+            Provider::Twitter => "twitter.com".into(),
+            Provider::Yahoo => "yahoo.com".into(),
+            Provider::Oidc(name) => prefixed("oidc.", name),
+            Provider::Saml(name) => prefixed("saml.", name),
+        }
+    }
+
+    /// Fails with `AuthError::InvalidProviderId` for an OIDC or SAML provider without a name.
+    pub fn validate(&self) -> Result<(), AuthError> {
+        match self {
+            Provider::Oidc(name) => unprefixed("oidc.", name).map(drop),
+            Provider::Saml(name) => unprefixed("saml.", name).map(drop),
+            _ => Ok(()),
+        }
+    }
+}
+
+fn prefixed(prefix: &str, name: &str) -> String {
+    if name.starts_with(prefix) {
+        name.into()
+    } else {
+        format!("{}{}", prefix, name)
+    }
+}
+
+/// The name of `name` without `prefix`, an empty name is invalid.
+fn unprefixed(prefix: &str, name: &str) -> Result<String, AuthError> {
+    let name = name.strip_prefix(prefix).unwrap_or(name).trim();
+    if name.is_empty() {
+        return Err(AuthError::InvalidProviderId);
+    }
+    Ok(name.into())
+}
+
+impl Serialize for Provider {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.serialize_str(&self.provider_id())
+    }
+}
+
+/// The `prompt` OAuth parameter, what the provider asks the user again.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Prompt {
+    /// Fails instead of showing anything, if the user isn't signed in to the provider.
+    None,
+    Consent,
+    SelectAccount,
+    Login,
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize)]
+struct CustomParameters {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    prompt: Option<Prompt>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    login_hint: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    hd: Option<String>,
+}
+
+impl CustomParameters {
+    fn is_empty(&self) -> bool {
+        *self == CustomParameters::default()
+    }
+}
+
+/// The options of `sign_in_with_provider`, a `Provider` can be passed as is.
+///
+/// ```
+/// use firebase_auth_lite::{OauthFlowOptions, Prompt, Provider};
+///
+/// let options = OauthFlowOptions::new(Provider::Google)
+///     .with_scope("https://www.googleapis.com/auth/calendar.readonly")
+///     .with_prompt(Prompt::SelectAccount)
+///     .with_hosted_domain("example.com");
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct OauthFlowOptions {
+    provider: Provider,
+    #[serde(
+        rename = "oauthScope",
+        serialize_with = "serialize_scopes",
+        skip_serializing_if = "Vec::is_empty"
+    )]
+    scopes: Vec<String>,
+    #[serde(
+        rename = "customParameter",
+        skip_serializing_if = "CustomParameters::is_empty"
+    )]
+    custom_parameters: CustomParameters,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    context: Option<String>,
+    #[serde(skip_serializing_if = "std::ops::Not::not")]
+    link_account: bool,
+}
+
+// `createAuthUri` takes the scopes space-delimited.
+fn serialize_scopes<S: Serializer>(scopes: &[String], serializer: S) -> Result<S::Ok, S::Error> {
+    serializer.serialize_str(&scopes.join(" "))
+}
+
+impl OauthFlowOptions {
+    pub fn new(provider: Provider) -> Self {
+        OauthFlowOptions {
+            provider,
+            scopes: Vec::new(),
+            custom_parameters: CustomParameters::default(),
+            context: None,
+            link_account: false,
+        }
+    }
+
+    /// Asks for an OAuth scope besides the default ones of the provider.
+    pub fn with_scope(mut self, scope: &str) -> Self {
+        if !self.scopes.iter().any(|s| s == scope) {
+            self.scopes.push(scope.into());
+        }
+        self
+    }
+
+    pub fn with_prompt(mut self, prompt: Prompt) -> Self {
+        self.custom_parameters.prompt = Some(prompt);
+        self
+    }
+
+    /// Prefills the email or username on the sign-in page of the provider.
+    pub fn with_login_hint(mut self, login_hint: &str) -> Self {
+        self.custom_parameters.login_hint = Some(login_hint.into());
+        self
+    }
+
+    /// Only lets accounts of the Google Workspace `domain` sign in, Google only.
+    pub fn with_hosted_domain(mut self, domain: &str) -> Self {
+        self.custom_parameters.hd = Some(domain.into());
+        self
+    }
+
+    /// An opaque value that is returned when the flow finishes.
+    pub fn with_context(mut self, context: &str) -> Self {
+        self.context = Some(context.into());
+        self
+    }
+
+    /// Links the provider to the signed-in user instead of signing in.
+    pub fn with_link_account(mut self, link_account: bool) -> Self {
+        self.link_account = link_account;
+        self
+    }
+
+    pub fn provider(&self) -> &Provider {
+        &self.provider
+    }
+
+    pub fn scopes(&self) -> &[String] {
+        &self.scopes
+    }
+}
+
+impl From<Provider> for OauthFlowOptions {
+    fn from(provider: Provider) -> Self {
+        OauthFlowOptions::new(provider)
+    }
+}
+
+mod sealed {
+    pub trait Sealed {}
+}
+
+/// A type per `Provider`, for `OauthFlowBuilder`.
+pub trait ProviderType: sealed::Sealed {
+    fn provider(self) -> Provider;
+}
+
+/// A provider signed in with OAuth, it takes custom OAuth parameters.
+pub trait OauthProvider: ProviderType {}
+
+/// An OAuth provider that takes OAuth scopes.
+pub trait ScopedProvider: OauthProvider {}
+
+macro_rules! provider_types {
+    ($($(#[$doc:meta])* $name:ident, $($kind:ident),*;)*) => {
+        $(
+            $(#[$doc])*
+            #[derive(Debug, Clone, Copy, PartialEq, Eq)]
+            pub struct $name;
+
+            impl sealed::Sealed for $name {}
+
+            impl ProviderType for $name {
+                fn provider(self) -> Provider {
+                    Provider::$name
+                }
+            }
+
+            $(impl $kind for $name {})*
+        )*
+    };
+}
+
+provider_types! {
+    Google, OauthProvider, ScopedProvider;
+    GitHub, OauthProvider, ScopedProvider;
+    Facebook, OauthProvider, ScopedProvider;
+    Microsoft, OauthProvider, ScopedProvider;
+    Apple, OauthProvider, ScopedProvider;
+    /// Twitter uses OAuth 1.0, which has no scopes.
+    Twitter, OauthProvider;
+    Yahoo, OauthProvider, ScopedProvider;
+}
+
+/// `Provider::Oidc`, with a name checked by `new`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Oidc(String);
+
+impl Oidc {
+    /// `name` is the provider ID with or without its `oidc.` prefix.
+    pub fn new(name: &str) -> Result<Self, AuthError> {
+        unprefixed("oidc.", name).map(Oidc)
+    }
+}
+
+impl sealed::Sealed for Oidc {}
+
+impl ProviderType for Oidc {
+    fn provider(self) -> Provider {
+        Provider::Oidc(self.0)
+    }
+}
+
+impl OauthProvider for Oidc {}
+
+impl ScopedProvider for Oidc {}
+
+/// `Provider::Saml`, with a name checked by `new`. It takes neither scopes nor OAuth parameters.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Saml(String);
+
+impl Saml {
+    /// `name` is the provider ID with or without its `saml.` prefix.
+    pub fn new(name: &str) -> Result<Self, AuthError> {
+        unprefixed("saml.", name).map(Saml)
+    }
+}
+
+impl sealed::Sealed for Saml {}
+
+impl ProviderType for Saml {
+    fn provider(self) -> Provider {
+        Provider::Saml(self.0)
+    }
+}
+
+/// Builds `OauthFlowOptions` with only the options `P` supports.
+///
+/// ```
+/// use firebase_auth_lite::{provider::{Google, OauthFlowBuilder}, OauthFlowOptions, Prompt};
+///
+/// let options: OauthFlowOptions = OauthFlowBuilder::new(Google)
+///     .with_scope("https://www.googleapis.com/auth/calendar.readonly")
+///     .with_prompt(Prompt::SelectAccount)
+///     .with_hosted_domain("example.com")
+///     .into();
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct OauthFlowBuilder<P> {
+    options: OauthFlowOptions,
+    provider: PhantomData<P>,
+}
+
+impl<P: ProviderType> OauthFlowBuilder<P> {
+    pub fn new(provider: P) -> Self {
+        OauthFlowBuilder {
+            options: OauthFlowOptions::new(provider.provider()),
+            provider: PhantomData,
+        }
+    }
+
+    /// An opaque value that is returned when the flow finishes.
+    pub fn with_context(mut self, context: &str) -> Self {
+        self.options = self.options.with_context(context);
+        self
+    }
+
+    /// Links the provider to the signed-in user instead of signing in.
+    pub fn with_link_account(mut self, link_account: bool) -> Self {
+        self.options = self.options.with_link_account(link_account);
+        self
+    }
+}
+
+impl<P: OauthProvider> OauthFlowBuilder<P> {
+    pub fn with_prompt(mut self, prompt: Prompt) -> Self {
+        self.options = self.options.with_prompt(prompt);
+        self
+    }
+
+    /// Prefills the email or username on the sign-in page of the provider.
+    pub fn with_login_hint(mut self, login_hint: &str) -> Self {
+        self.options = self.options.with_login_hint(login_hint);
+        self
+    }
+}
+
+impl<P: ScopedProvider> OauthFlowBuilder<P> {
+    /// Asks for an OAuth scope besides the default ones of the provider.
+    pub fn with_scope(mut self, scope: &str) -> Self {
+        self.options = self.options.with_scope(scope);
+        self
+    }
+}
+
+impl OauthFlowBuilder<Google> {
+    /// Only lets accounts of the Google Workspace `domain` sign in.
+    pub fn with_hosted_domain(mut self, domain: &str) -> Self {
+        self.options = self.options.with_hosted_domain(domain);
+        self
+    }
+}
+
+impl<P> From<OauthFlowBuilder<P>> for OauthFlowOptions {
+    fn from(builder: OauthFlowBuilder<P>) -> Self {
+        builder.options
+    }
+}
@@ -0,0 +1,86 @@
+use crate::{Auth, IdToken, UserProfile};
+use chrono::{DateTime, Duration, Utc};
+use futures::{
+    future::{self, AbortHandle},
+    FutureExt, StreamExt,
+};
+use gloo_timers::future::TimeoutFuture;
+
+/// How long to wait before trying again when a refresh fails, e.g. while offline.
+const RETRY_DELAY_SECONDS: i64 = 30;
+
+/// Refreshing never happens more often than this, even when `margin`
+/// is longer than the lifetime of the ID token.
+const MIN_DELAY_SECONDS: i64 = 5;
+
+impl Auth {
+    /// Refreshes the ID token in the background `margin` before it expires, so it is
+    /// always valid, e.g. for a long-lived websocket connection that has to re-authenticate.
+    ///
+    /// The refresher follows the signed-in user and stops when the returned value is dropped.
+    /// `margin` should be well under the hour an ID token is valid for.
+    pub fn start_token_refresher(&self, margin: Duration) -> TokenRefresher {
+        let (task, handle) = future::abortable(refresh_loop(self.clone(), margin));
+        wasm_bindgen_futures::spawn_local(task.map(|_| ()));
+
+        TokenRefresher { handle }
+    }
+}
+
+/// Keeps the background refresher started by [`Auth::start_token_refresher`] running until it is dropped.
+pub struct TokenRefresher {
+    handle: AbortHandle,
+}
+
+impl Drop for TokenRefresher {
+    fn drop(&mut self) {
+        self.handle.abort();
+    }
+}
+
+async fn refresh_loop(auth: Auth, margin: Duration) {
+    let mut auth_state = auth.auth_state_stream().fuse();
+
+    loop {
+        // An unreadable session can't be refreshed either.
+        let user = auth.current_user().ok().flatten();
+        let Some(expires_at) = user.as_ref().and_then(expires_at) else {
+            // Wait for a user to sign in.
+            match auth_state.next().await {
+                Some(_) => continue,
+                None => return,
+            }
+        };
+
+        let delay = (expires_at - margin - Utc::now()).max(Duration::seconds(MIN_DELAY_SECONDS));
+
+        // Start over when the user changes, the new one has another token.
+        futures::select! {
+            _ = sleep(delay).fuse() => {
+                if auth.get_id_token(true).await.is_err() {
+                    sleep(Duration::seconds(RETRY_DELAY_SECONDS)).await;
+                }
+            }
+            user = auth_state.next() => {
+                if user.is_none() {
+                    return;
+                }
+            }
+        }
+    }
+}
+
+/// `tokenManager.expiresAt` isn't persisted, so a restored session falls back to the `exp` claim.
+fn expires_at(user: &UserProfile) -> Option<DateTime<Utc>> {
+    let token_manager = user.token_manager.as_ref()?;
+    token_manager.expires_at.or_else(|| {
+        IdToken::decode(&token_manager.id_token)
+            .ok()
+            .map(|id_token| id_token.expires_at())
+    })
+}
+
+async fn sleep(duration: Duration) {
+    let millis = duration.num_milliseconds().clamp(0, u32::MAX as i64) as u32;
+    TimeoutFuture::new(millis).await;
+}
@@ -0,0 +1,140 @@
+use crate::{from_js, Auth, AuthError, IdToken, UserProfile};
+use futures::{
+    channel::mpsc::{self, UnboundedReceiver},
+    Stream,
+};
+use std::{
+    pin::Pin,
+    task::{Context, Poll},
+};
+use wasm_bindgen::prelude::*;
+
+/// `Auth::js_listen` or `Auth::js_listen_id_token`.
+type Listen = fn(&Auth, &Closure<dyn FnMut(JsValue)>) -> js_sys::Function;
+
+impl Auth {
+    /// Calls `callback` whenever a user signs in or out, including from another tab.
+    ///
+    /// If the session was already restored from storage, `callback` is also
+    /// called right away with the current user. The callback is unsubscribed
+    /// when the returned listener is dropped.
+    ///
+    /// A user that can't be read is passed as an error, not as signed out.
+    pub fn on_auth_state_changed<F>(&self, callback: F) -> AuthStateListener
+    where
+        F: FnMut(Result<Option<UserProfile>, AuthError>) + 'static,
+    {
+        self.subscribe(Auth::js_listen, callback)
+    }
+
+    /// Like [`Auth::on_auth_state_changed`], but yields the user from a `Stream`.
+    pub fn auth_state_stream(&self) -> AuthStateStream {
+        let (sender, receiver) = mpsc::unbounded();
+
+        let listener = self.on_auth_state_changed(move |user| {
+            let _ = sender.unbounded_send(user);
+        });
+
+        AuthStateStream {
+            _listener: listener,
+            receiver,
+        }
+    }
+
+    /// Calls `callback` whenever the ID token changes, on sign-in, sign-out and
+    /// every token refresh, e.g. to re-authenticate a realtime connection.
+    ///
+    /// Like [`Auth::on_auth_state_changed`], `callback` is also called right away
+    /// if the session was already restored from storage.
+    pub fn on_id_token_changed<F>(&self, mut callback: F) -> AuthStateListener
+    where
+        F: FnMut(Result<Option<IdToken>, AuthError>) + 'static,
+    {
+        self.subscribe(Auth::js_listen_id_token, move |user| {
+            let id_token = user.and_then(|user| {
+                user.and_then(|user| user.token_manager)
+                    .map(|token_manager| IdToken::decode(&token_manager.id_token))
+                    .transpose()
+            });
+            callback(id_token)
+        })
+    }
+
+    /// Like [`Auth::on_id_token_changed`], but yields the ID token from a `Stream`.
+    pub fn id_token_stream(&self) -> IdTokenStream {
+        let (sender, receiver) = mpsc::unbounded();
+
+        let listener = self.on_id_token_changed(move |id_token| {
+            let _ = sender.unbounded_send(id_token);
+        });
+
+        IdTokenStream {
+            _listener: listener,
+            receiver,
+        }
+    }
+
+    /// Subscribes `callback` with one of the `listen` methods of `main.js`.
+    fn subscribe<F>(&self, listen: Listen, mut callback: F) -> AuthStateListener
+    where
+        F: FnMut(Result<Option<UserProfile>, AuthError>) + 'static,
+    {
+        let current = self.js_user();
+        let restored = !current.is_undefined();
+
+        let closure = Closure::new(move |user: JsValue| callback(from_js(user)));
+        let unsubscribe = listen(self, &closure);
+
+        if restored {
+            let _ = closure
+                .as_ref()
+                .unchecked_ref::<js_sys::Function>()
+                .call1(&JsValue::NULL, &current);
+        }
+
+        AuthStateListener {
+            unsubscribe,
+            _closure: closure,
+        }
+    }
+}
+
+/// Keeps an auth state or ID token callback subscribed until it is dropped.
+pub struct AuthStateListener {
+    unsubscribe: js_sys::Function,
+    _closure: Closure<dyn FnMut(JsValue)>,
+}
+
+impl Drop for AuthStateListener {
+    fn drop(&mut self) {
+        let _ = self.unsubscribe.call0(&JsValue::NULL);
+    }
+}
+
+/// A `Stream` of the signed-in user, `None` after signing out.
+pub struct AuthStateStream {
+    _listener: AuthStateListener,
+    receiver: UnboundedReceiver<Result<Option<UserProfile>, AuthError>>,
+}
+
+impl Stream for AuthStateStream {
+    type Item = Result<Option<UserProfile>, AuthError>;
+
+    fn poll_next(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<Self::Item>> {
+        Pin::new(&mut self.receiver).poll_next(cx)
+    }
+}
+
+/// A `Stream` of the ID token of the signed-in user, `None` after signing out.
+pub struct IdTokenStream {
+    _listener: AuthStateListener,
+    receiver: UnboundedReceiver<Result<Option<IdToken>, AuthError>>,
+}
+
+impl Stream for IdTokenStream {
+    type Item = Result<Option<IdToken>, AuthError>;
+
+    fn poll_next(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<Self::Item>> {
+        Pin::new(&mut self.receiver).poll_next(cx)
+    }
+}
@@ -0,0 +1,309 @@
+use crate::AuthError;
+use async_trait::async_trait;
+use js_sys::{Object, Promise, Reflect};
+use std::{cell::RefCell, collections::HashMap, rc::Rc};
+use wasm_bindgen::{prelude::*, JsCast};
+use wasm_bindgen_futures::{future_to_promise, JsFuture};
+use web_sys::{IdbDatabase, IdbObjectStore, IdbRequest, IdbTransactionMode};
+
+/// Where `Auth` persists the session and the data it needs across redirects.
+///
+/// Keys are namespaced by `Auth`, so one storage can be shared by several instances.
+#[async_trait(?Send)]
+pub trait AuthStorage {
+    async fn get(&self, key: &str) -> Result<Option<String>, AuthError>;
+
+    async fn set(&self, key: &str, value: &str) -> Result<(), AuthError>;
+
+    async fn remove(&self, key: &str) -> Result<(), AuthError>;
+
+    /// Whether other tabs see the same data, so a sign-in or sign-out in
+    /// one of them should update every `Auth` through the `storage` event.
+    fn syncs_across_tabs(&self) -> bool {
+        false
+    }
+}
+
+/// How long the signed-in state lasts, like the official SDK's persistence modes.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum Persistence {
+    /// Survives reloads and is shared by every tab, using `localStorage`.
+    #[default]
+    Local,
+    /// Lasts only as long as the tab, using `sessionStorage`.
+    Session,
+    /// Lives only in memory and is lost on reload.
+    None,
+}
+
+/// Builds the `{ get, set, remove }` adapter object the `Auth` constructor in `main.js` expects.
+pub(crate) fn storage_adapter(storage: Rc<dyn AuthStorage>) -> JsValue {
+    let adapter = Object::new();
+
+    let get = {
+        let storage = storage.clone();
+        Closure::<dyn Fn(String) -> Promise>::new(move |key: String| {
+            let storage = storage.clone();
+            future_to_promise(async move {
+                let value = storage.get(&key).await.map_err(to_js_error)?;
+                Ok(value.map(JsValue::from).unwrap_or(JsValue::NULL))
+            })
+        })
+    };
+
+    let set = {
+        let storage = storage.clone();
+        Closure::<dyn Fn(String, JsValue) -> Promise>::new(move |key: String, value: JsValue| {
+            let storage = storage.clone();
+            future_to_promise(async move {
+                // `main.js` also stores booleans, which `localStorage` would turn into strings.
+                let value = match value.as_string() {
+                    Some(value) => value,
+                    None => js_sys::JSON::stringify(&value)?.into(),
+                };
+                storage.set(&key, &value).await.map_err(to_js_error)?;
+                Ok(JsValue::UNDEFINED)
+            })
+        })
+    };
+
+    let remove = Closure::<dyn Fn(String) -> Promise>::new(move |key: String| {
+        let storage = storage.clone();
+        future_to_promise(async move {
+            storage.remove(&key).await.map_err(to_js_error)?;
+            Ok(JsValue::UNDEFINED)
+        })
+    });
+
+    for (name, closure) in [
+        ("get", get.into_js_value()),
+        ("set", set.into_js_value()),
+        ("remove", remove.into_js_value()),
+    ] {
+        let _ = Reflect::set(&adapter, &name.into(), &closure);
+    }
+
+    adapter.into()
+}
+
+fn to_js_error(error: AuthError) -> JsValue {
+    js_sys::Error::new(&error.to_string()).into()
+}
+
+fn storage_error(error: JsValue) -> AuthError {
+    AuthError::Storage(match error.dyn_ref::<js_sys::Error>() {
+        Some(error) => error.message().into(),
+        None => format!("{:?}", error),
+    })
+}
+
+fn window() -> Result<web_sys::Window, AuthError> {
+    web_sys::window().ok_or_else(|| AuthError::Storage("No window is available".into()))
+}
+
+fn web_storage(
+    storage: Result<Option<web_sys::Storage>, JsValue>,
+) -> Result<web_sys::Storage, AuthError> {
+    storage
+        .map_err(storage_error)?
+        .ok_or_else(|| AuthError::Storage("Web Storage is not available".into()))
+}
+
+/// Persists the session in `localStorage`, shared by every tab. This is the default.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct LocalStorage;
+
+#[async_trait(?Send)]
+impl AuthStorage for LocalStorage {
+    async fn get(&self, key: &str) -> Result<Option<String>, AuthError> {
+        web_storage(window()?.local_storage())?
+            .get_item(key)
+            .map_err(storage_error)
+    }
+
+    async fn set(&self, key: &str, value: &str) -> Result<(), AuthError> {
+        web_storage(window()?.local_storage())?
+            .set_item(key, value)
+            .map_err(storage_error)
+    }
+
+    async fn remove(&self, key: &str) -> Result<(), AuthError> {
+        web_storage(window()?.local_storage())?
+            .remove_item(key)
+            .map_err(storage_error)
+    }
+
+    fn syncs_across_tabs(&self) -> bool {
+        true
+    }
+}
+
+/// Persists the session in `sessionStorage`, so it only lasts as long as the tab.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct SessionStorage;
+
+#[async_trait(?Send)]
+impl AuthStorage for SessionStorage {
+    async fn get(&self, key: &str) -> Result<Option<String>, AuthError> {
+        web_storage(window()?.session_storage())?
+            .get_item(key)
+            .map_err(storage_error)
+    }
+
+    async fn set(&self, key: &str, value: &str) -> Result<(), AuthError> {
+        web_storage(window()?.session_storage())?
+            .set_item(key, value)
+            .map_err(storage_error)
+    }
+
+    async fn remove(&self, key: &str) -> Result<(), AuthError> {
+        web_storage(window()?.session_storage())?
+            .remove_item(key)
+            .map_err(storage_error)
+    }
+}
+
+/// Keeps the session in memory, it is lost on reload.
+///
+/// Clones share the same data.
+#[derive(Debug, Clone, Default)]
+pub struct MemoryStorage {
+    items: Rc<RefCell<HashMap<String, String>>>,
+}
+
+impl MemoryStorage {
+    pub fn new() -> Self {
+        MemoryStorage::default()
+    }
+}
+
+#[async_trait(?Send)]
+impl AuthStorage for MemoryStorage {
+    async fn get(&self, key: &str) -> Result<Option<String>, AuthError> {
+        Ok(self.items.borrow().get(key).cloned())
+    }
+
+    async fn set(&self, key: &str, value: &str) -> Result<(), AuthError> {
+        self.items.borrow_mut().insert(key.into(), value.into());
+        Ok(())
+    }
+
+    async fn remove(&self, key: &str) -> Result<(), AuthError> {
+        self.items.borrow_mut().remove(key);
+        Ok(())
+    }
+}
+
+const INDEXED_DB_STORE: &str = "auth";
+
+/// Persists the session in an IndexedDB database, which has a larger quota than Web Storage.
+#[derive(Debug, Clone)]
+pub struct IndexedDbStorage {
+    name: String,
+    db: Rc<RefCell<Option<IdbDatabase>>>,
+}
+
+impl IndexedDbStorage {
+    /// Uses the database `name`, which is created on first use.
+    pub fn new(name: &str) -> Self {
+        IndexedDbStorage {
+            name: name.into(),
+            db: Rc::default(),
+        }
+    }
+
+    async fn db(&self) -> Result<IdbDatabase, AuthError> {
+        if let Some(db) = self.db.borrow().as_ref() {
+            return Ok(db.clone());
+        }
+
+        let factory = window()?
+            .indexed_db()
+            .map_err(storage_error)?
+            .ok_or_else(|| AuthError::Storage("IndexedDB is not available".into()))?;
+        let request = factory
+            .open_with_u32(&self.name, 1)
+            .map_err(storage_error)?;
+
+        let on_upgrade_needed = {
+            let request = request.clone();
+            Closure::once_into_js(move || {
+                if let Ok(db) = request.result() {
+                    let _ = db
+                        .unchecked_into::<IdbDatabase>()
+                        .create_object_store(INDEXED_DB_STORE);
+                }
+            })
+        };
+        request.set_onupgradeneeded(Some(on_upgrade_needed.unchecked_ref()));
+
+        let db: IdbDatabase = idb_request(&request).await?.unchecked_into();
+        *self.db.borrow_mut() = Some(db.clone());
+        Ok(db)
+    }
+
+    async fn store(&self, mode: IdbTransactionMode) -> Result<IdbObjectStore, AuthError> {
+        self.db()
+            .await?
+            .transaction_with_str_and_mode(INDEXED_DB_STORE, mode)
+            .and_then(|transaction| transaction.object_store(INDEXED_DB_STORE))
+            .map_err(storage_error)
+    }
+}
+
+#[async_trait(?Send)]
+impl AuthStorage for IndexedDbStorage {
+    async fn get(&self, key: &str) -> Result<Option<String>, AuthError> {
+        let request = self
+            .store(IdbTransactionMode::Readonly)
+            .await?
+            .get(&key.into())
+            .map_err(storage_error)?;
+        Ok(idb_request(&request).await?.as_string())
+    }
+
+    async fn set(&self, key: &str, value: &str) -> Result<(), AuthError> {
+        let request = self
+            .store(IdbTransactionMode::Readwrite)
+            .await?
+            .put_with_key(&value.into(), &key.into())
+            .map_err(storage_error)?;
+        idb_request(&request).await.map(|_| ())
+    }
+
+    async fn remove(&self, key: &str) -> Result<(), AuthError> {
+        let request = self
+            .store(IdbTransactionMode::Readwrite)
+            .await?
+            .delete(&key.into())
+            .map_err(storage_error)?;
+        idb_request(&request).await.map(|_| ())
+    }
+}
+
+/// Waits for an `IDBRequest` to succeed and returns its result.
+async fn idb_request(request: &IdbRequest) -> Result<JsValue, AuthError> {
+    let promise = Promise::new(&mut |resolve, reject| {
+        let on_success = {
+            let request = request.clone();
+            Closure::once_into_js(move || {
+                let result = request.result().unwrap_or(JsValue::UNDEFINED);
+                let _ = resolve.call1(&JsValue::NULL, &result);
+            })
+        };
+        let on_error = {
+            let request = request.clone();
+            Closure::once_into_js(move || {
+                let error = match request.error() {
+                    Ok(Some(error)) => error.into(),
+                    _ => JsValue::UNDEFINED,
+                };
+                let _ = reject.call1(&JsValue::NULL, &error);
+            })
+        };
+        request.set_onsuccess(Some(on_success.unchecked_ref()));
+        request.set_onerror(Some(on_error.unchecked_ref()));
+    });
+
+    JsFuture::from(promise).await.map_err(storage_error)
+}
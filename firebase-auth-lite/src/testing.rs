@@ -0,0 +1,517 @@
+//! An in-memory `AuthClient` for unit tests that shouldn't touch the network.
+//!
+//! ```
+//! # futures::executor::block_on(async {
+//! use firebase_auth_lite::{testing::FakeAuth, AuthClient, AuthError};
+//!
+//! let auth = FakeAuth::new();
+//! auth.add_user("user@example.com", "password");
+//!
+//! auth.fail_next(AuthError::TooManyAttemptsTryLater);
+//! assert!(auth.sign_in("user@example.com".into(), "password".into()).await.is_err());
+//! assert!(auth.sign_in("user@example.com".into(), "password".into()).await.is_ok());
+//! # });
+//! ```
+
+use crate::{
+    AuthClient, AuthError, AuthStreams, FirebaseClaims, IdToken, IdTokenClaims, ProfileAttribute,
+    ProfileUpdate, TokenManager, UserProfile,
+};
+use async_trait::async_trait;
+use base64::{engine::general_purpose::URL_SAFE_NO_PAD, Engine};
+use chrono::{DateTime, Duration, Utc};
+use futures::{
+    channel::mpsc::{self, UnboundedReceiver, UnboundedSender},
+    Stream,
+};
+use std::{
+    cell::RefCell,
+    collections::{HashMap, VecDeque},
+    pin::Pin,
+    rc::Rc,
+    task::{Context, Poll},
+};
+
+/// The `aud` of the ID tokens issued by `FakeAuth`.
+pub const FAKE_PROJECT_ID: &str = "fake-project";
+
+/// A fake `Auth` that keeps users in memory.
+///
+/// Clones share the same users and session.
+#[derive(Clone, Default)]
+pub struct FakeAuth {
+    state: Rc<RefCell<State>>,
+}
+
+#[derive(Default)]
+struct State {
+    users: HashMap<String, FakeUser>,
+    current: Option<UserProfile>,
+    failures: VecDeque<AuthError>,
+    clock_offset: Duration,
+    token_lifetime: Option<Duration>,
+    refresh_revoked: bool,
+    next_id: u64,
+    listeners: Vec<UnboundedSender<Result<Option<UserProfile>, AuthError>>>,
+    id_token_listeners: Vec<UnboundedSender<Result<Option<IdToken>, AuthError>>>,
+}
+
+struct FakeUser {
+    password: String,
+    profile: UserProfile,
+}
+
+impl FakeAuth {
+    pub fn new() -> Self {
+        FakeAuth::default()
+    }
+
+    /// Registers an email/password user and returns its profile.
+    pub fn add_user(&self, email: &str, password: &str) -> UserProfile {
+        let mut state = self.state.borrow_mut();
+        let profile = state.new_profile(Some(email));
+        state.users.insert(
+            email.into(),
+            FakeUser {
+                password: password.into(),
+                profile: profile.clone(),
+            },
+        );
+        profile
+    }
+
+    /// Disables a user, so signing in fails with `USER_DISABLED`.
+    pub fn disable_user(&self, email: &str) {
+        if let Some(user) = self.state.borrow_mut().users.get_mut(email) {
+            user.profile.disabled = true;
+        }
+    }
+
+    /// Sets the custom claims of a user, like `setCustomUserClaims` of the Admin SDK
+    /// they are in the ID token after the next sign-in or refresh.
+    pub fn set_custom_claims(&self, email: &str, claims: &serde_json::Value) {
+        let mut state = self.state.borrow_mut();
+        if let Some(user) = state.users.get_mut(email) {
+            user.profile.custom_attributes = Some(claims.to_string());
+        }
+        if let Some(user) = state
+            .current
+            .as_mut()
+            .filter(|user| user.email.as_deref() == Some(email))
+        {
+            user.custom_attributes = Some(claims.to_string());
+        }
+    }
+
+    /// Makes the next call through `AuthClient` fail with `error`.
+    ///
+    /// Queued errors are returned in order, one per call.
+    pub fn fail_next(&self, error: AuthError) {
+        self.state.borrow_mut().failures.push_back(error);
+    }
+
+    /// How long ID tokens are valid for, an hour by default.
+    pub fn set_token_lifetime(&self, lifetime: Duration) {
+        self.state.borrow_mut().token_lifetime = Some(lifetime);
+    }
+
+    /// Moves the fake clock forward, e.g. past the expiry of the ID token.
+    pub fn advance_time(&self, duration: Duration) {
+        self.state.borrow_mut().clock_offset += duration;
+    }
+
+    /// The time according to the fake clock.
+    pub fn now(&self) -> DateTime<Utc> {
+        self.state.borrow().now()
+    }
+
+    /// Makes refreshing an expired ID token fail with `TOKEN_EXPIRED`, like a revoked session.
+    pub fn revoke_refresh_tokens(&self) {
+        self.state.borrow_mut().refresh_revoked = true;
+    }
+
+    /// The signed-in user, like `AuthClient::current_user` but it can't fail.
+    pub fn current_user(&self) -> Option<UserProfile> {
+        self.state.borrow().current.clone()
+    }
+
+    /// Replaces the signed-in user and emits an auth state event,
+    /// like a sign-in or sign-out from another tab.
+    pub fn set_current_user(&self, user: Option<UserProfile>) {
+        self.state.borrow_mut().set_current(user);
+    }
+
+    /// A `Stream` of the signed-in user, starting with the current one.
+    pub fn auth_state_stream(&self) -> FakeAuthStateStream {
+        let (sender, receiver) = mpsc::unbounded();
+        let mut state = self.state.borrow_mut();
+
+        let _ = sender.unbounded_send(Ok(state.current.clone()));
+        state.listeners.push(sender);
+
+        FakeAuthStateStream { receiver }
+    }
+
+    /// A `Stream` of the ID token, starting with the current one,
+    /// that yields again on sign-in, sign-out and every refresh.
+    pub fn id_token_stream(&self) -> FakeIdTokenStream {
+        let (sender, receiver) = mpsc::unbounded();
+        let mut state = self.state.borrow_mut();
+
+        let _ = sender.unbounded_send(Ok(id_token(&state.current)));
+        state.id_token_listeners.push(sender);
+
+        FakeIdTokenStream { receiver }
+    }
+
+    fn check_failure(&self) -> Result<(), AuthError> {
+        match self.state.borrow_mut().failures.pop_front() {
+            Some(error) => Err(error),
+            None => Ok(()),
+        }
+    }
+
+    /// Refreshes the ID token if it has expired, like `enforceAuth` in `main.js`.
+    fn enforce_auth(&self) -> Result<UserProfile, AuthError> {
+        self.refresh_id_token(false)
+    }
+
+    fn refresh_id_token(&self, force_refresh: bool) -> Result<UserProfile, AuthError> {
+        let mut state = self.state.borrow_mut();
+        let user = state.current.clone().ok_or(AuthError::NotSignedIn)?;
+
+        let expired = user
+            .token_manager
+            .as_ref()
+            .and_then(|token_manager| token_manager.expires_at)
+            .is_none_or(|expires_at| state.now() >= expires_at);
+        if !expired && !force_refresh {
+            return Ok(user);
+        }
+
+        if state.refresh_revoked {
+            return Err(AuthError::TokenExpired);
+        }
+
+        let mut user = user;
+        user.token_manager = Some(state.new_token_manager(&user));
+        state.current = Some(user.clone());
+        state.emit_id_token();
+        Ok(user)
+    }
+}
+
+impl State {
+    fn now(&self) -> DateTime<Utc> {
+        Utc::now() + self.clock_offset
+    }
+
+    fn new_profile(&mut self, email: Option<&str>) -> UserProfile {
+        self.next_id += 1;
+        let now = self.now();
+
+        UserProfile {
+            local_id: format!("fake-user-{}", self.next_id),
+            email: email.map(String::from),
+            email_verified: false,
+            display_name: None,
+            photo_url: None,
+            phone_number: None,
+            disabled: false,
+            password_hash: None,
+            password_updated_at: email.map(|_| now),
+            valid_since: Some(now),
+            last_login_at: None,
+            created_at: Some(now),
+            last_refresh_at: None,
+            custom_attributes: None,
+            custom_auth: false,
+            provider_user_info: Vec::new(),
+            mfa_info: Vec::new(),
+            tenant_id: None,
+            token_manager: None,
+        }
+    }
+
+    /// Issues an unsigned JWT with the claims of `user`, so it can be decoded like a real one.
+    fn new_token_manager(&mut self, user: &UserProfile) -> TokenManager {
+        self.next_id += 1;
+        let now = self.now();
+
+        let identities = user
+            .email
+            .iter()
+            .map(|email| ("email".to_string(), vec![email.clone()]))
+            .collect();
+        let claims = IdTokenClaims {
+            sub: user.local_id.clone(),
+            aud: FAKE_PROJECT_ID.into(),
+            iss: format!("https://securetoken.google.com/{}", FAKE_PROJECT_ID),
+            exp: now + self.token_lifetime.unwrap_or(Duration::hours(1)),
+            iat: now,
+            auth_time: user.last_login_at.unwrap_or(now),
+            user_id: Some(user.local_id.clone()),
+            email: user.email.clone(),
+            email_verified: user.email_verified,
+            name: user.display_name.clone(),
+            picture: user.photo_url.clone(),
+            phone_number: user.phone_number.clone(),
+            firebase: FirebaseClaims {
+                sign_in_provider: if user.is_anonymous() {
+                    "anonymous".into()
+                } else {
+                    "password".into()
+                },
+                tenant: user.tenant_id.clone(),
+                identities,
+            },
+            custom: user.custom_attributes().ok().flatten().unwrap_or_default(),
+        };
+
+        TokenManager {
+            id_token: encode_jwt(&claims),
+            refresh_token: format!("fake-refresh-token-{}-{}", user.local_id, self.next_id),
+            expires_at: Some(claims.exp),
+        }
+    }
+
+    fn sign_in_as(&mut self, email: &str) -> UserProfile {
+        let mut profile = self.users[email].profile.clone();
+        profile.last_login_at = Some(self.now());
+        profile.token_manager = Some(self.new_token_manager(&profile));
+
+        self.set_current(Some(profile.clone()));
+        profile
+    }
+
+    fn set_current(&mut self, user: Option<UserProfile>) {
+        let id_token_changed = id_token(&self.current) != id_token(&user);
+        self.current = user;
+
+        let current = &self.current;
+        self.listeners
+            .retain(|listener| listener.unbounded_send(Ok(current.clone())).is_ok());
+
+        if id_token_changed {
+            self.emit_id_token();
+        }
+    }
+
+    fn emit_id_token(&mut self) {
+        let id_token = id_token(&self.current);
+        self.id_token_listeners
+            .retain(|listener| listener.unbounded_send(Ok(id_token.clone())).is_ok());
+    }
+}
+
+#[async_trait(?Send)]
+impl AuthClient for FakeAuth {
+    async fn sign_in(&self, email: String, password: String) -> Result<UserProfile, AuthError> {
+        self.check_failure()?;
+        let mut state = self.state.borrow_mut();
+
+        let user = state.users.get(&email).ok_or(AuthError::EmailNotFound)?;
+        if user.password != password {
+            return Err(AuthError::InvalidPassword);
+        }
+        if user.profile.disabled {
+            return Err(AuthError::UserDisabled);
+        }
+
+        Ok(state.sign_in_as(&email))
+    }
+
+    async fn sign_up(&self, email: String, password: String) -> Result<UserProfile, AuthError> {
+        self.check_failure()?;
+
+        if self.state.borrow().users.contains_key(&email) {
+            return Err(AuthError::EmailExists);
+        }
+        if password.len() < 6 {
+            return Err(AuthError::WeakPassword);
+        }
+
+        self.add_user(&email, &password);
+        Ok(self.state.borrow_mut().sign_in_as(&email))
+    }
+
+    async fn sign_in_anonymously(&self) -> Result<UserProfile, AuthError> {
+        self.check_failure()?;
+        let mut state = self.state.borrow_mut();
+
+        // Anonymous users aren't stored, they can't sign in again once signed out.
+        let mut profile = state.new_profile(None);
+        profile.last_login_at = Some(state.now());
+        profile.token_manager = Some(state.new_token_manager(&profile));
+
+        state.set_current(Some(profile.clone()));
+        Ok(profile)
+    }
+
+    async fn sign_out(&self) -> Result<(), AuthError> {
+        self.check_failure()?;
+        self.state.borrow_mut().set_current(None);
+        Ok(())
+    }
+
+    fn current_user(&self) -> Result<Option<UserProfile>, AuthError> {
+        Ok(FakeAuth::current_user(self))
+    }
+
+    async fn get_id_token(&self, force_refresh: bool) -> Result<IdToken, AuthError> {
+        self.check_failure()?;
+
+        let id_token = self
+            .refresh_id_token(force_refresh)?
+            .token_manager
+            .map(|token_manager| token_manager.id_token)
+            .ok_or(AuthError::NotSignedIn)?;
+        IdToken::decode(&id_token)
+    }
+
+    async fn update_profile(&self, update: ProfileUpdate) -> Result<UserProfile, AuthError> {
+        self.check_failure()?;
+        let user = self.enforce_auth()?;
+        let mut state = self.state.borrow_mut();
+
+        if let Some(email) = &update.email {
+            if state
+                .users
+                .get(email)
+                .is_some_and(|stored| stored.profile.local_id != user.local_id)
+            {
+                return Err(AuthError::EmailExists);
+            }
+        }
+        if update
+            .password
+            .as_ref()
+            .is_some_and(|password| password.len() < 6)
+        {
+            return Err(AuthError::WeakPassword);
+        }
+
+        let new_tokens = update.email.is_some() || update.password.is_some();
+        let stored = user
+            .email
+            .as_ref()
+            .and_then(|email| state.users.remove(email));
+
+        let mut profile = user;
+        if let Some(email) = update.email {
+            profile.email = Some(email);
+            profile.email_verified = false;
+        }
+        if update.password.is_some() {
+            profile.password_updated_at = Some(state.now());
+        }
+        if let Some(display_name) = update.display_name {
+            profile.display_name = Some(display_name);
+        }
+        if let Some(photo_url) = update.photo_url {
+            profile.photo_url = Some(photo_url);
+        }
+        for attribute in update.delete_attributes {
+            match attribute {
+                ProfileAttribute::DisplayName => profile.display_name = None,
+                ProfileAttribute::PhotoUrl => profile.photo_url = None,
+            }
+        }
+        // Like the real API, changing the credentials issues new tokens.
+        if new_tokens {
+            profile.token_manager = Some(state.new_token_manager(&profile));
+        }
+
+        // Anonymous users are only stored once they have an email and a password.
+        let password = update.password.or(stored.map(|stored| stored.password));
+        if let (Some(email), Some(password)) = (profile.email.clone(), password) {
+            let mut stored_profile = profile.clone();
+            stored_profile.token_manager = None;
+            state.users.insert(
+                email,
+                FakeUser {
+                    password,
+                    profile: stored_profile,
+                },
+            );
+        }
+
+        state.set_current(Some(profile.clone()));
+        Ok(profile)
+    }
+
+    async fn link_with_email_password(
+        &self,
+        email: String,
+        password: String,
+    ) -> Result<UserProfile, AuthError> {
+        self.update_profile(
+            ProfileUpdate::new()
+                .with_email(&email)
+                .with_password(&password),
+        )
+        .await
+    }
+
+    async fn delete_account(&self) -> Result<(), AuthError> {
+        self.check_failure()?;
+        let user = self.enforce_auth()?;
+
+        let mut state = self.state.borrow_mut();
+        if let Some(email) = &user.email {
+            state.users.remove(email);
+        }
+        state.set_current(None);
+        Ok(())
+    }
+}
+
+impl AuthStreams for FakeAuth {
+    type AuthStateStream = FakeAuthStateStream;
+    type IdTokenStream = FakeIdTokenStream;
+
+    fn auth_state_stream(&self) -> FakeAuthStateStream {
+        FakeAuth::auth_state_stream(self)
+    }
+
+    fn id_token_stream(&self) -> FakeIdTokenStream {
+        FakeAuth::id_token_stream(self)
+    }
+}
+
+fn id_token(user: &Option<UserProfile>) -> Option<IdToken> {
+    let token_manager = user.as_ref()?.token_manager.as_ref()?;
+    IdToken::decode(&token_manager.id_token).ok()
+}
+
+fn encode_jwt(claims: &IdTokenClaims) -> String {
+    let header = URL_SAFE_NO_PAD.encode(r#"{"alg":"none","typ":"JWT"}"#);
+    let payload = URL_SAFE_NO_PAD.encode(serde_json::to_vec(claims).unwrap_or_default());
+    format!("{}.{}.", header, payload)
+}
+
+/// A `Stream` of the signed-in user of a `FakeAuth`.
+pub struct FakeAuthStateStream {
+    receiver: UnboundedReceiver<Result<Option<UserProfile>, AuthError>>,
+}
+
+impl Stream for FakeAuthStateStream {
+    type Item = Result<Option<UserProfile>, AuthError>;
+
+    fn poll_next(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<Self::Item>> {
+        Pin::new(&mut self.receiver).poll_next(cx)
+    }
+}
+
+/// A `Stream` of the ID token of a `FakeAuth`.
+pub struct FakeIdTokenStream {
+    receiver: UnboundedReceiver<Result<Option<IdToken>, AuthError>>,
+}
+
+impl Stream for FakeIdTokenStream {
+    type Item = Result<Option<IdToken>, AuthError>;
+
+    fn poll_next(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<Self::Item>> {
+        Pin::new(&mut self.receiver).poll_next(cx)
+    }
+}
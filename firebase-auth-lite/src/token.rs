@@ -0,0 +1,113 @@
+use crate::AuthError;
+use base64::{engine::general_purpose::URL_SAFE_NO_PAD, Engine};
+use chrono::{DateTime, Utc};
+use serde::{de::DeserializeOwned, Deserialize, Serialize};
+use std::{collections::HashMap, fmt};
+
+/// A Firebase ID token, the JWT sent as `Authorization: Bearer <idToken>`.
+///
+/// The payload is decoded without checking the signature, it is only meant
+/// for the client to read its own claims, a backend must verify the token.
+#[derive(Debug, Clone, PartialEq)]
+pub struct IdToken {
+    raw: String,
+    claims: IdTokenClaims,
+}
+
+/// The claims of a Firebase ID token.
+#[derive(Debug, Clone, PartialEq, Deserialize, Serialize)]
+pub struct IdTokenClaims {
+    /// The uid of the user, the same as `UserProfile::local_id`.
+    pub sub: String,
+    /// The Firebase project ID.
+    pub aud: String,
+    /// `https://securetoken.google.com/<project ID>`.
+    pub iss: String,
+    #[serde(with = "chrono::serde::ts_seconds")]
+    pub exp: DateTime<Utc>,
+    #[serde(with = "chrono::serde::ts_seconds")]
+    pub iat: DateTime<Utc>,
+    /// When the user signed in, it doesn't change when the token is refreshed.
+    #[serde(with = "chrono::serde::ts_seconds")]
+    pub auth_time: DateTime<Utc>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub user_id: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub email: Option<String>,
+    #[serde(default)]
+    pub email_verified: bool,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub name: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub picture: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub phone_number: Option<String>,
+    pub firebase: FirebaseClaims,
+    /// The custom claims set by an admin backend, e.g. `{ "admin": true }`.
+    #[serde(flatten)]
+    pub custom: serde_json::Map<String, serde_json::Value>,
+}
+
+/// The `firebase` claim of an ID token.
+#[derive(Debug, Clone, PartialEq, Deserialize, Serialize)]
+pub struct FirebaseClaims {
+    /// How the user signed in, e.g. `password`, `google.com`, `custom` or `anonymous`.
+    pub sign_in_provider: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub tenant: Option<String>,
+    /// The identifiers of the user at each linked provider, e.g. `{ "email": ["user@example.com"] }`.
+    #[serde(default)]
+    pub identities: HashMap<String, Vec<String>>,
+}
+
+impl IdToken {
+    /// Decodes the payload of `raw`, without checking the signature.
+    pub fn decode(raw: &str) -> Result<Self, AuthError> {
+        let payload = raw.split('.').nth(1).ok_or(AuthError::InvalidIdToken)?;
+        let payload = URL_SAFE_NO_PAD
+            .decode(payload.trim_end_matches('='))
+            .map_err(|_| AuthError::InvalidIdToken)?;
+        let claims = serde_json::from_slice(&payload).map_err(|_| AuthError::InvalidIdToken)?;
+
+        Ok(IdToken {
+            raw: raw.into(),
+            claims,
+        })
+    }
+
+    /// The encoded token.
+    pub fn as_str(&self) -> &str {
+        &self.raw
+    }
+
+    pub fn claims(&self) -> &IdTokenClaims {
+        &self.claims
+    }
+
+    pub fn expires_at(&self) -> DateTime<Utc> {
+        self.claims.exp
+    }
+
+    pub fn is_expired(&self) -> bool {
+        Utc::now() >= self.claims.exp
+    }
+}
+
+impl IdTokenClaims {
+    /// Parses the custom claims, e.g. into a struct with the roles set by an admin backend.
+    pub fn custom_claims<T: DeserializeOwned>(&self) -> Result<T, serde_json::Error> {
+        serde_json::from_value(self.custom.clone().into())
+    }
+}
+
+impl fmt::Display for IdToken {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&self.raw)
+    }
+}
+
+impl From<IdToken> for String {
+    fn from(token: IdToken) -> Self {
+        token.raw
+    }
+}
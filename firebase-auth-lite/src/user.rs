@@ -0,0 +1,343 @@
+use chrono::{DateTime, Utc};
+use serde::{de::DeserializeOwned, Deserialize, Serialize};
+
+/// A user as returned by the identitytoolkit `accounts:lookup` endpoint,
+/// along with the `tokenManager` that `main.js` attaches to it.
+///
+/// Only `local_id` is guaranteed to be present, anonymous, custom token and
+/// OAuth users are missing most of the email/password fields.
+#[derive(Debug, Clone, PartialEq, Deserialize, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct UserProfile {
+    pub local_id: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub email: Option<String>,
+    #[serde(default)]
+    pub email_verified: bool,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub display_name: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub photo_url: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub phone_number: Option<String>,
+    #[serde(default)]
+    pub disabled: bool,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub password_hash: Option<String>,
+    #[serde(
+        default,
+        with = "timestamp::millis_number",
+        skip_serializing_if = "Option::is_none"
+    )]
+    pub password_updated_at: Option<DateTime<Utc>>,
+    #[serde(
+        default,
+        with = "timestamp::seconds",
+        skip_serializing_if = "Option::is_none"
+    )]
+    pub valid_since: Option<DateTime<Utc>>,
+    #[serde(
+        default,
+        with = "timestamp::millis",
+        skip_serializing_if = "Option::is_none"
+    )]
+    pub last_login_at: Option<DateTime<Utc>>,
+    #[serde(
+        default,
+        with = "timestamp::millis",
+        skip_serializing_if = "Option::is_none"
+    )]
+    pub created_at: Option<DateTime<Utc>>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub last_refresh_at: Option<DateTime<Utc>>,
+    /// The custom claims set by an admin backend, as a JSON encoded string.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub custom_attributes: Option<String>,
+    #[serde(default)]
+    pub custom_auth: bool,
+    #[serde(default)]
+    pub provider_user_info: Vec<ProviderUserInfo>,
+    #[serde(default)]
+    pub mfa_info: Vec<MfaInfo>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub tenant_id: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub token_manager: Option<TokenManager>,
+}
+
+impl UserProfile {
+    /// Parses the JSON encoded `custom_attributes`.
+    pub fn custom_attributes<T: DeserializeOwned>(&self) -> Result<Option<T>, serde_json::Error> {
+        self.custom_attributes
+            .as_deref()
+            .map(serde_json::from_str)
+            .transpose()
+    }
+
+    /// Whether the user signed in anonymously and hasn't linked any credential yet.
+    ///
+    /// `accounts:lookup` has no such field, an account is anonymous when
+    /// it has no email, phone number, custom token or linked provider.
+    pub fn is_anonymous(&self) -> bool {
+        self.email.is_none()
+            && self.phone_number.is_none()
+            && !self.custom_auth
+            && self.provider_user_info.is_empty()
+    }
+
+    /// The ids of the providers linked to this account, e.g. `password` or `google.com`.
+    pub fn provider_ids(&self) -> impl Iterator<Item = &str> {
+        self.provider_user_info
+            .iter()
+            .map(|info| info.provider_id.as_str())
+    }
+}
+
+/// The changes to make to the signed-in user, serialized as an `accounts:update` request body.
+///
+/// ```
+/// use firebase_auth_lite::{ProfileAttribute, ProfileUpdate};
+///
+/// let update = ProfileUpdate::new()
+///     .with_display_name("Example User")
+///     .delete_attribute(ProfileAttribute::PhotoUrl);
+/// ```
+#[derive(Debug, Clone, Default, PartialEq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ProfileUpdate {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub(crate) display_name: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub(crate) photo_url: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub(crate) email: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub(crate) password: Option<String>,
+    #[serde(rename = "deleteAttribute", skip_serializing_if = "Vec::is_empty")]
+    pub(crate) delete_attributes: Vec<ProfileAttribute>,
+}
+
+/// A profile field that `ProfileUpdate::delete_attribute` can clear.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "SCREAMING_SNAKE_CASE")]
+pub enum ProfileAttribute {
+    DisplayName,
+    PhotoUrl,
+}
+
+impl ProfileUpdate {
+    pub fn new() -> Self {
+        ProfileUpdate::default()
+    }
+
+    pub fn with_display_name(mut self, display_name: &str) -> Self {
+        self.display_name = Some(display_name.into());
+        self
+    }
+
+    pub fn with_photo_url(mut self, photo_url: &str) -> Self {
+        self.photo_url = Some(photo_url.into());
+        self
+    }
+
+    /// Changes the email, it has to be verified again afterwards.
+    pub fn with_email(mut self, email: &str) -> Self {
+        self.email = Some(email.into());
+        self
+    }
+
+    /// Changes the password, which also issues a new ID token.
+    pub fn with_password(mut self, password: &str) -> Self {
+        self.password = Some(password.into());
+        self
+    }
+
+    /// Clears `attribute`, instead of setting it to an empty string.
+    pub fn delete_attribute(mut self, attribute: ProfileAttribute) -> Self {
+        if !self.delete_attributes.contains(&attribute) {
+            self.delete_attributes.push(attribute);
+        }
+        self
+    }
+}
+
+/// Information about one of the Identity Providers linked to an account.
+#[derive(Debug, Clone, PartialEq, Deserialize, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ProviderUserInfo {
+    pub provider_id: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub federated_id: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub raw_id: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub email: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub display_name: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub photo_url: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub phone_number: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub screen_name: Option<String>,
+}
+
+/// A second factor enrolled by the user.
+#[derive(Debug, Clone, PartialEq, Deserialize, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct MfaInfo {
+    pub mfa_enrollment_id: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub display_name: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub phone_info: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub enrolled_at: Option<DateTime<Utc>>,
+}
+
+/// The credentials of the signed-in user.
+#[derive(Debug, Clone, PartialEq, Deserialize, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct TokenManager {
+    pub id_token: String,
+    pub refresh_token: String,
+    /// When the `id_token` expires.
+    ///
+    /// `main.js` doesn't persist this value, so it is missing
+    /// for a session restored from storage until the first refresh.
+    #[serde(
+        default,
+        with = "timestamp::millis_number",
+        skip_serializing_if = "Option::is_none"
+    )]
+    pub expires_at: Option<DateTime<Utc>>,
+}
+
+/// The identitytoolkit API encodes timestamps either as milliseconds or
+/// seconds since the epoch, sometimes as a number and sometimes as a string.
+mod timestamp {
+    use chrono::{DateTime, TimeZone, Utc};
+    use serde::{de, Deserializer, Serializer};
+    use std::fmt;
+
+    struct EpochVisitor {
+        millis_per_unit: i64,
+    }
+
+    impl EpochVisitor {
+        fn to_date_time<E: de::Error>(&self, value: i64) -> Result<Option<DateTime<Utc>>, E> {
+            value
+                .checked_mul(self.millis_per_unit)
+                .and_then(|millis| Utc.timestamp_millis_opt(millis).single())
+                .map(Some)
+                .ok_or_else(|| E::custom(format!("timestamp out of range: {}", value)))
+        }
+    }
+
+    impl<'de> de::Visitor<'de> for EpochVisitor {
+        type Value = Option<DateTime<Utc>>;
+
+        fn expecting(&self, f: &mut fmt::Formatter) -> fmt::Result {
+            write!(f, "a timestamp as a number or a string")
+        }
+
+        fn visit_i64<E: de::Error>(self, value: i64) -> Result<Self::Value, E> {
+            self.to_date_time(value)
+        }
+
+        fn visit_u64<E: de::Error>(self, value: u64) -> Result<Self::Value, E> {
+            let value = i64::try_from(value).map_err(E::custom)?;
+            self.to_date_time(value)
+        }
+
+        fn visit_f64<E: de::Error>(self, value: f64) -> Result<Self::Value, E> {
+            self.to_date_time(value as i64)
+        }
+
+        fn visit_str<E: de::Error>(self, value: &str) -> Result<Self::Value, E> {
+            self.to_date_time(value.parse().map_err(E::custom)?)
+        }
+
+        fn visit_none<E: de::Error>(self) -> Result<Self::Value, E> {
+            Ok(None)
+        }
+
+        fn visit_unit<E: de::Error>(self) -> Result<Self::Value, E> {
+            Ok(None)
+        }
+
+        fn visit_some<D: Deserializer<'de>>(self, d: D) -> Result<Self::Value, D::Error> {
+            d.deserialize_any(self)
+        }
+    }
+
+    fn deserialize<'de, D: Deserializer<'de>>(
+        d: D,
+        millis_per_unit: i64,
+    ) -> Result<Option<DateTime<Utc>>, D::Error> {
+        d.deserialize_option(EpochVisitor { millis_per_unit })
+    }
+
+    /// Milliseconds encoded as a string, e.g. `createdAt`.
+    pub mod millis {
+        use super::*;
+
+        pub fn serialize<S: Serializer>(
+            value: &Option<DateTime<Utc>>,
+            s: S,
+        ) -> Result<S::Ok, S::Error> {
+            match value {
+                Some(t) => s.serialize_str(&t.timestamp_millis().to_string()),
+                None => s.serialize_none(),
+            }
+        }
+
+        pub fn deserialize<'de, D: Deserializer<'de>>(
+            d: D,
+        ) -> Result<Option<DateTime<Utc>>, D::Error> {
+            super::deserialize(d, 1)
+        }
+    }
+
+    /// Milliseconds encoded as a number, e.g. `passwordUpdatedAt`.
+    pub mod millis_number {
+        use super::*;
+
+        pub fn serialize<S: Serializer>(
+            value: &Option<DateTime<Utc>>,
+            s: S,
+        ) -> Result<S::Ok, S::Error> {
+            match value {
+                Some(t) => s.serialize_i64(t.timestamp_millis()),
+                None => s.serialize_none(),
+            }
+        }
+
+        pub fn deserialize<'de, D: Deserializer<'de>>(
+            d: D,
+        ) -> Result<Option<DateTime<Utc>>, D::Error> {
+            super::deserialize(d, 1)
+        }
+    }
+
+    /// Seconds encoded as a string, e.g. `validSince`.
+    pub mod seconds {
+        use super::*;
+
+        pub fn serialize<S: Serializer>(
+            value: &Option<DateTime<Utc>>,
+            s: S,
+        ) -> Result<S::Ok, S::Error> {
+            match value {
+                Some(t) => s.serialize_str(&t.timestamp().to_string()),
+                None => s.serialize_none(),
+            }
+        }
+
+        pub fn deserialize<'de, D: Deserializer<'de>>(
+            d: D,
+        ) -> Result<Option<DateTime<Utc>>, D::Error> {
+            super::deserialize(d, 1000)
+        }
+    }
+}
@@ -0,0 +1,299 @@
+//! Verifies Firebase ID tokens on a backend, e.g. the `Authorization: Bearer <idToken>`
+//! header that `AuthorizedClient` adds to every request.
+//!
+//! The checks follow <https://firebase.google.com/docs/auth/admin/verify-id-tokens>.
+//!
+//! ```no_run
+//! # async fn handle(bearer: &str) -> Result<(), firebase_auth_lite::verify::VerifyError> {
+//! use firebase_auth_lite::verify::IdTokenVerifier;
+//!
+//! let verifier = IdTokenVerifier::new("my-project-id");
+//! let claims = verifier.verify(bearer).await?;
+//! println!("Request from {}", claims.sub);
+//! # Ok(())
+//! # }
+//! ```
+
+use crate::IdTokenClaims;
+use async_trait::async_trait;
+use chrono::{DateTime, Duration, Utc};
+use futures::lock::Mutex as AsyncMutex;
+use jsonwebtoken::{errors::ErrorKind, jwk::JwkSet, Algorithm, Validation};
+use std::{collections::HashMap, fmt, sync::Mutex};
+
+pub use jsonwebtoken::DecodingKey;
+
+/// Google's public keys for the ID tokens of every Firebase project, as a JWK set.
+pub const GOOGLE_JWKS_URL: &str =
+    "https://www.googleapis.com/service_accounts/v1/jwk/securetoken@system.gserviceaccount.com";
+
+/// How far the clocks of Google and the backend may be apart.
+const CLOCK_SKEW_SECONDS: i64 = 60;
+
+/// Why an ID token was rejected.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum VerifyError {
+    /// The token isn't a JWT with the claims of an ID token.
+    Malformed(String),
+    /// The token isn't signed with RS256.
+    InvalidAlgorithm,
+    /// None of the public keys has the `kid` of the token.
+    UnknownKey(String),
+    InvalidSignature,
+    Expired,
+    /// The token is issued in the future.
+    InvalidIssuedAt,
+    /// The user signed in in the future.
+    InvalidAuthTime,
+    /// The token is for another project.
+    InvalidAudience,
+    InvalidIssuer,
+    /// The uid is empty or longer than 128 characters.
+    InvalidSubject,
+    /// The public keys couldn't be fetched.
+    KeySource(String),
+}
+
+impl fmt::Display for VerifyError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            VerifyError::Malformed(reason) => write!(f, "The ID token is malformed: {}", reason),
+            VerifyError::InvalidAlgorithm => write!(f, "The ID token isn't signed with RS256"),
+            VerifyError::UnknownKey(kid) => write!(f, "No public key has the id {}", kid),
+            VerifyError::InvalidSignature => write!(f, "The ID token signature is invalid"),
+            VerifyError::Expired => write!(f, "The ID token has expired"),
+            VerifyError::InvalidIssuedAt => write!(f, "The ID token is issued in the future"),
+            VerifyError::InvalidAuthTime => {
+                write!(f, "The ID token has an authentication time in the future")
+            }
+            VerifyError::InvalidAudience => write!(f, "The ID token is for another project"),
+            VerifyError::InvalidIssuer => write!(f, "The ID token has an invalid issuer"),
+            VerifyError::InvalidSubject => write!(f, "The ID token has an invalid subject"),
+            VerifyError::KeySource(reason) => {
+                write!(f, "The public keys couldn't be fetched: {}", reason)
+            }
+        }
+    }
+}
+
+impl std::error::Error for VerifyError {}
+
+/// Where the public keys that sign the ID tokens come from.
+///
+/// Unlike the rest of the crate this trait is `Send`, so a verifier can be shared by the
+/// handlers of a multi-threaded server.
+#[async_trait]
+pub trait KeySource: Send + Sync {
+    /// The public key with the `kid` of the token header.
+    async fn key(&self, kid: &str) -> Result<DecodingKey, VerifyError>;
+}
+
+/// Fetches Google's public keys, and caches them for as long as the `Cache-Control` header allows.
+pub struct GooglePublicKeys {
+    http: reqwest::Client,
+    url: String,
+    cache: Mutex<Option<CachedKeys>>,
+    // Held while fetching, so concurrent calls don't make more than one request.
+    fetch: AsyncMutex<()>,
+}
+
+struct CachedKeys {
+    keys: HashMap<String, DecodingKey>,
+    expires_at: DateTime<Utc>,
+}
+
+impl GooglePublicKeys {
+    pub fn new() -> Self {
+        GooglePublicKeys::with_url(GOOGLE_JWKS_URL)
+    }
+
+    /// Fetches the JWK set from `url` instead, e.g. a proxy.
+    pub fn with_url(url: &str) -> Self {
+        GooglePublicKeys {
+            http: reqwest::Client::new(),
+            url: url.into(),
+            cache: Mutex::new(None),
+            fetch: AsyncMutex::new(()),
+        }
+    }
+
+    fn cached(&self, kid: &str) -> Option<Option<DecodingKey>> {
+        let cache = self.cache.lock().unwrap();
+        let cache = cache
+            .as_ref()
+            .filter(|cache| Utc::now() < cache.expires_at)?;
+        Some(cache.keys.get(kid).cloned())
+    }
+
+    /// Fetches the keys, they are returned as well as cached, since `max-age` may be 0.
+    async fn refresh(&self) -> Result<HashMap<String, DecodingKey>, VerifyError> {
+        let response = self
+            .http
+            .get(&self.url)
+            .send()
+            .await
+            .and_then(|response| response.error_for_status())
+            .map_err(|e| VerifyError::KeySource(e.to_string()))?;
+
+        let max_age = response
+            .headers()
+            .get(reqwest::header::CACHE_CONTROL)
+            .and_then(|value| value.to_str().ok())
+            .and_then(max_age)
+            .unwrap_or(0);
+
+        let jwks: JwkSet = response
+            .json()
+            .await
+            .map_err(|e| VerifyError::KeySource(e.to_string()))?;
+        let keys: HashMap<_, _> = jwks
+            .keys
+            .iter()
+            .filter_map(|jwk| {
+                let kid = jwk.common.key_id.clone()?;
+                let key = DecodingKey::from_jwk(jwk).ok()?;
+                Some((kid, key))
+            })
+            .collect();
+
+        *self.cache.lock().unwrap() = Some(CachedKeys {
+            keys: keys.clone(),
+            expires_at: Utc::now() + Duration::seconds(max_age),
+        });
+        Ok(keys)
+    }
+}
+
+impl Default for GooglePublicKeys {
+    fn default() -> Self {
+        GooglePublicKeys::new()
+    }
+}
+
+#[async_trait]
+impl KeySource for GooglePublicKeys {
+    async fn key(&self, kid: &str) -> Result<DecodingKey, VerifyError> {
+        if let Some(key) = self.cached(kid) {
+            return key.ok_or_else(|| VerifyError::UnknownKey(kid.into()));
+        }
+
+        let _guard = self.fetch.lock().await;
+
+        // Another call may have fetched the keys while we were waiting.
+        let key = match self.cached(kid) {
+            Some(key) => key,
+            None => self.refresh().await?.remove(kid),
+        };
+        key.ok_or_else(|| VerifyError::UnknownKey(kid.into()))
+    }
+}
+
+/// A fixed set of public keys, e.g. for tests that sign their own tokens.
+#[derive(Clone, Default)]
+pub struct StaticKeys {
+    keys: HashMap<String, DecodingKey>,
+}
+
+impl StaticKeys {
+    pub fn new() -> Self {
+        StaticKeys::default()
+    }
+
+    pub fn with_key(mut self, kid: &str, key: DecodingKey) -> Self {
+        self.keys.insert(kid.into(), key);
+        self
+    }
+
+    /// Adds a PEM encoded RSA public key.
+    pub fn with_rsa_pem(self, kid: &str, pem: &[u8]) -> Result<Self, VerifyError> {
+        let key =
+            DecodingKey::from_rsa_pem(pem).map_err(|e| VerifyError::KeySource(e.to_string()))?;
+        Ok(self.with_key(kid, key))
+    }
+}
+
+#[async_trait]
+impl KeySource for StaticKeys {
+    async fn key(&self, kid: &str) -> Result<DecodingKey, VerifyError> {
+        self.keys
+            .get(kid)
+            .cloned()
+            .ok_or_else(|| VerifyError::UnknownKey(kid.into()))
+    }
+}
+
+/// Verifies the ID tokens of one Firebase project.
+pub struct IdTokenVerifier<K = GooglePublicKeys> {
+    project_id: String,
+    keys: K,
+}
+
+impl IdTokenVerifier<GooglePublicKeys> {
+    /// Verifies the tokens with Google's public keys.
+    pub fn new(project_id: &str) -> Self {
+        IdTokenVerifier::with_key_source(project_id, GooglePublicKeys::new())
+    }
+}
+
+impl<K: KeySource> IdTokenVerifier<K> {
+    pub fn with_key_source(project_id: &str, keys: K) -> Self {
+        IdTokenVerifier {
+            project_id: project_id.into(),
+            keys,
+        }
+    }
+
+    /// Checks the signature and the claims of `token` and returns the claims.
+    pub async fn verify(&self, token: &str) -> Result<IdTokenClaims, VerifyError> {
+        let header = jsonwebtoken::decode_header(token)
+            .map_err(|e| VerifyError::Malformed(e.to_string()))?;
+        if header.alg != Algorithm::RS256 {
+            return Err(VerifyError::InvalidAlgorithm);
+        }
+        let kid = header
+            .kid
+            .ok_or_else(|| VerifyError::Malformed("missing kid".into()))?;
+        let key = self.keys.key(&kid).await?;
+
+        let mut validation = Validation::new(Algorithm::RS256);
+        validation.leeway = CLOCK_SKEW_SECONDS as u64;
+        validation.set_audience(&[&self.project_id]);
+        validation.set_issuer(&[format!(
+            "https://securetoken.google.com/{}",
+            self.project_id
+        )]);
+        validation.set_required_spec_claims(&["exp", "iat", "aud", "iss", "sub"]);
+
+        let claims = jsonwebtoken::decode::<IdTokenClaims>(token, &key, &validation)
+            .map_err(|e| match e.kind() {
+                ErrorKind::InvalidSignature => VerifyError::InvalidSignature,
+                ErrorKind::ExpiredSignature => VerifyError::Expired,
+                ErrorKind::InvalidAudience => VerifyError::InvalidAudience,
+                ErrorKind::InvalidIssuer => VerifyError::InvalidIssuer,
+                ErrorKind::InvalidAlgorithm => VerifyError::InvalidAlgorithm,
+                _ => VerifyError::Malformed(e.to_string()),
+            })?
+            .claims;
+
+        let latest = Utc::now() + Duration::seconds(CLOCK_SKEW_SECONDS);
+        if claims.iat > latest {
+            return Err(VerifyError::InvalidIssuedAt);
+        }
+        if claims.auth_time > latest {
+            return Err(VerifyError::InvalidAuthTime);
+        }
+        if claims.sub.is_empty() || claims.sub.len() > 128 {
+            return Err(VerifyError::InvalidSubject);
+        }
+
+        Ok(claims)
+    }
+}
+
+/// Parses the `max-age` directive of a `Cache-Control` header, in seconds.
+fn max_age(cache_control: &str) -> Option<i64> {
+    cache_control
+        .split(',')
+        .filter_map(|directive| directive.trim().strip_prefix("max-age="))
+        .find_map(|seconds| seconds.parse().ok())
+}
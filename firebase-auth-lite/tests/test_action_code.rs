@@ -0,0 +1,116 @@
+use firebase_auth_lite::{
+    ActionCodeInfo, ActionCodeOperation, ActionCodeSettings, EmailAction, EmailActionMode,
+    OobRequest,
+};
+use serde_json::json;
+
+#[test]
+fn test_oob_request_types() {
+    assert_eq!(OobRequest::PasswordReset.as_str(), "PASSWORD_RESET");
+    assert_eq!(OobRequest::VerifyEmail.as_str(), "VERIFY_EMAIL");
+    assert_eq!(OobRequest::EmailSignIn.as_str(), "EMAIL_SIGNIN");
+    assert_eq!(
+        OobRequest::VerifyAndChangeEmail.to_string(),
+        "VERIFY_AND_CHANGE_EMAIL"
+    );
+}
+
+#[test]
+fn test_action_code_settings_body() {
+    let settings = ActionCodeSettings::new("https://example.com/finish")
+        .with_handle_code_in_app(true)
+        .with_ios_bundle_id("com.example.ios")
+        .with_android_package_name("com.example.android", true)
+        .with_dynamic_link_domain("example.page.link");
+
+    assert_eq!(
+        serde_json::to_value(&settings).unwrap(),
+        json!({
+            "continueUrl": "https://example.com/finish",
+            "canHandleCodeInApp": true,
+            "iOSBundleId": "com.example.ios",
+            "androidPackageName": "com.example.android",
+            "androidInstallApp": true,
+            "dynamicLinkDomain": "example.page.link",
+        })
+    );
+    assert_eq!(
+        serde_json::to_value(ActionCodeSettings::new("https://example.com")).unwrap(),
+        json!({ "continueUrl": "https://example.com" })
+    );
+}
+
+#[test]
+fn test_action_code_info() {
+    let info: ActionCodeInfo = serde_json::from_value(json!({
+        "kind": "identitytoolkit#ResetPasswordResponse",
+        "requestType": "VERIFY_AND_CHANGE_EMAIL",
+        "email": "old@example.com",
+        "newEmail": "new@example.com",
+    }))
+    .unwrap();
+    assert_eq!(info.operation, ActionCodeOperation::VerifyAndChangeEmail);
+    assert_eq!(info.email, "old@example.com");
+    assert_eq!(info.new_email.as_deref(), Some("new@example.com"));
+
+    let info: ActionCodeInfo = serde_json::from_value(json!({
+        "requestType": "EMAIL_SIGNIN",
+        "email": "user@example.com",
+    }))
+    .unwrap();
+    assert_eq!(info.operation, ActionCodeOperation::EmailSignIn);
+    assert_eq!(info.new_email, None);
+
+    let info: ActionCodeInfo = serde_json::from_value(json!({
+        "requestType": "SOMETHING_NEW",
+        "email": "user@example.com",
+    }))
+    .unwrap();
+    assert_eq!(info.operation, ActionCodeOperation::Unknown);
+}
+
+#[test]
+fn test_email_action_parse() {
+    let action = EmailAction::parse(
+        "https://example.com/auth/action?mode=verifyEmail&oobCode=code&apiKey=key&continueUrl=https%3A%2F%2Fexample.com%2Fhome&lang=fr",
+    )
+    .unwrap();
+    assert_eq!(
+        action,
+        EmailAction {
+            mode: EmailActionMode::VerifyEmail,
+            oob_code: "code".into(),
+            continue_url: Some("https://example.com/home".into()),
+            lang: Some("fr".into()),
+        }
+    );
+
+    let modes = [
+        ("resetPassword", EmailActionMode::ResetPassword),
+        ("recoverEmail", EmailActionMode::RecoverEmail),
+        ("signIn", EmailActionMode::SignIn),
+        (
+            "verifyAndChangeEmail",
+            EmailActionMode::VerifyAndChangeEmail,
+        ),
+    ];
+    for (mode, expected) in modes {
+        let link = format!("https://example.com/auth/action?mode={}&oobCode=code", mode);
+        let action = EmailAction::parse(&link).unwrap();
+        assert_eq!(action.mode, expected);
+        assert_eq!(action.continue_url, None);
+    }
+}
+
+#[test]
+fn test_email_action_parse_invalid() {
+    assert_eq!(EmailAction::parse("not a link"), None);
+    assert_eq!(
+        EmailAction::parse("https://example.com/auth/action?mode=verifyEmail"),
+        None
+    );
+    assert_eq!(
+        EmailAction::parse("https://example.com/auth/action?mode=somethingNew&oobCode=code"),
+        None
+    );
+}
@@ -1,4 +1,9 @@
-use firebase_auth_lite::{Auth, AuthOptions, OauthFlowOptions};
+use firebase_auth_lite::{
+    ActionCodeSettings, Auth, AuthError, AuthOptions, AuthStorage, AuthorizedClient, MemoryStorage,
+    OobRequest, Persistence, ProfileUpdate, Provider,
+};
+use futures::StreamExt;
+use wasm_bindgen::JsValue;
 use wasm_bindgen_test::*;
 
 wasm_bindgen_test_configure!(run_in_browser);
@@ -25,9 +30,15 @@ async fn test_auth_sign_in_with_provider() {
     let auth = Auth::new(AuthOptions::new("not-an-api-key"));
 
     assert!(auth
-        .sign_in_with_provider(OauthFlowOptions::new("".into(), "".into(), true))
+        .sign_in_with_provider(Provider::Google.into())
         .await
         .is_err());
+    assert_eq!(
+        auth.sign_in_with_provider(Provider::Oidc("".into()).into())
+            .await
+            .err(),
+        Some(AuthError::InvalidProviderId)
+    );
 }
 
 #[wasm_bindgen_test(async)]
@@ -42,7 +53,7 @@ async fn test_auth_sign_up() {
     let auth = Auth::new(AuthOptions::new("not-an-api-key"));
 
     assert!(auth
-        .signUp("example@example.com".into(), "example".into())
+        .sign_up("example@example.com".into(), "example".into())
         .await
         .is_err());
 }
@@ -62,9 +73,52 @@ async fn test_auth_send_oob_code() {
     let auth = Auth::new(AuthOptions::new("not-an-api-key"));
 
     assert!(auth
-        .send_oob_code("EMAIL_SIGNIN".into(), "example@example.com".into())
+        .send_oob_code(OobRequest::EmailSignIn, "example@example.com".into())
         .await
-        .is_ok());
+        .is_err());
+}
+
+#[wasm_bindgen_test(async)]
+async fn test_auth_send_oob_code_network_error() {
+    let auth = Auth::new(AuthOptions::new("not-an-api-key").with_emulator("127.0.0.1:1"));
+    let settings = ActionCodeSettings::new("https://example.com/finish");
+
+    assert!(matches!(
+        auth.send_oob_code_with_settings(
+            OobRequest::PasswordReset,
+            "example@example.com".into(),
+            &settings
+        )
+        .await,
+        Err(AuthError::Network(_))
+    ));
+}
+
+#[wasm_bindgen_test(async)]
+async fn test_auth_send_sign_in_link_network_error() {
+    let storage = MemoryStorage::new();
+    let auth = Auth::new(
+        AuthOptions::new("not-an-api-key")
+            .with_name("sign-in-link")
+            .with_emulator("127.0.0.1:1")
+            .with_storage(storage.clone()),
+    );
+    let settings =
+        ActionCodeSettings::new("https://example.com/finish").with_handle_code_in_app(true);
+
+    assert!(matches!(
+        auth.send_sign_in_link("example@example.com".into(), &settings)
+            .await,
+        Err(AuthError::Network(_))
+    ));
+    // The email is only remembered once the link was sent.
+    assert_eq!(
+        storage
+            .get("Auth:EmailForSignIn:not-an-api-key:sign-in-link")
+            .await
+            .unwrap(),
+        None
+    );
 }
 
 #[wasm_bindgen_test(async)]
@@ -77,6 +131,20 @@ async fn test_auth_reset_password() {
         .is_err());
 }
 
+#[wasm_bindgen_test(async)]
+async fn test_auth_check_action_code() {
+    let auth = Auth::new(AuthOptions::new("not-an-api-key"));
+
+    assert!(auth
+        .check_action_code("fake-oob-code".into())
+        .await
+        .is_err());
+    assert!(auth
+        .apply_action_code("fake-oob-code".into())
+        .await
+        .is_err());
+}
+
 #[wasm_bindgen_test(async)]
 async fn test_auth_fetch_providers_for_email() {
     let auth = Auth::new(AuthOptions::new("not-an-api-key"));
@@ -91,22 +159,220 @@ async fn test_auth_fetch_providers_for_email() {
 async fn test_auth_fetch_profile() {
     let auth = Auth::new(AuthOptions::new("not-an-api-key"));
 
-    assert!(auth.fetch_profile().await.is_err());
+    assert_eq!(
+        auth.fetch_profile().await.err(),
+        Some(AuthError::NotSignedIn)
+    );
 }
 
 #[wasm_bindgen_test(async)]
 async fn test_auth_update_profile() {
     let auth = Auth::new(AuthOptions::new("not-an-api-key"));
 
-    assert!(auth
-        .update_profile(js_sys::Object::default())
-        .await
-        .is_err());
+    assert_eq!(
+        auth.update_profile(ProfileUpdate::new()).await.err(),
+        Some(AuthError::NotSignedIn)
+    );
+}
+
+#[wasm_bindgen_test(async)]
+async fn test_auth_link_with_email_password() {
+    let auth = Auth::new(AuthOptions::new("not-an-api-key"));
+
+    assert_eq!(
+        auth.link_with_email_password("user@example.com".into(), "password".into())
+            .await
+            .err(),
+        Some(AuthError::NotSignedIn)
+    );
 }
 
 #[wasm_bindgen_test(async)]
 async fn test_auth_delete_account() {
     let auth = Auth::new(AuthOptions::new("not-an-api-key"));
 
-    assert!(auth.delete_account().await.is_err());
+    assert_eq!(
+        auth.delete_account().await.err(),
+        Some(AuthError::NotSignedIn)
+    );
+}
+
+#[wasm_bindgen_test(async)]
+async fn test_auth_state_stream() {
+    let auth = Auth::new(AuthOptions::new("not-an-api-key"));
+    let mut stream = auth.auth_state_stream();
+
+    assert!(auth.sign_out().await.is_ok());
+    assert_eq!(stream.next().await, Some(Ok(None)));
+}
+
+#[wasm_bindgen_test(async)]
+async fn test_auth_state_stream_unreadable_user() {
+    let storage = MemoryStorage::new();
+    storage
+        .set("Auth:User:not-an-api-key:unreadable", r#"{"localId":42}"#)
+        .await
+        .unwrap();
+    let auth = Auth::new(
+        AuthOptions::new("not-an-api-key")
+            .with_name("unreadable")
+            .with_storage(storage),
+    );
+    let mut stream = auth.auth_state_stream();
+
+    // The restored session isn't reported as signed out.
+    assert!(matches!(stream.next().await, Some(Err(_))));
+    assert!(auth.current_user().is_err());
+}
+
+#[wasm_bindgen_test(async)]
+async fn test_auth_on_auth_state_changed_unsubscribes_on_drop() {
+    let auth = Auth::new(AuthOptions::new("not-an-api-key"));
+    let calls = std::rc::Rc::new(std::cell::Cell::new(0));
+
+    let listener = {
+        let calls = calls.clone();
+        auth.on_auth_state_changed(move |_| calls.set(calls.get() + 1))
+    };
+    assert!(auth.sign_out().await.is_ok());
+    let after_sign_out = calls.get();
+    assert!(after_sign_out > 0);
+
+    drop(listener);
+    assert!(auth.sign_out().await.is_ok());
+    assert_eq!(calls.get(), after_sign_out);
+}
+
+#[wasm_bindgen_test(async)]
+async fn test_auth_on_id_token_changed_after_sign_out() {
+    let auth = Auth::new(AuthOptions::new("not-an-api-key"));
+    assert!(auth.sign_out().await.is_ok());
+
+    let calls = std::rc::Rc::new(std::cell::RefCell::new(Vec::new()));
+    let _listener = {
+        let calls = calls.clone();
+        auth.on_id_token_changed(move |id_token| calls.borrow_mut().push(id_token))
+    };
+    assert_eq!(*calls.borrow(), vec![Ok(None)]);
+
+    // Signing out again doesn't change the token.
+    assert!(auth.sign_out().await.is_ok());
+    assert_eq!(calls.borrow().len(), 1);
+}
+
+#[wasm_bindgen_test]
+fn test_auth_error_from_type_error() {
+    let fetch_failed = js_sys::TypeError::new("Failed to fetch");
+    assert_eq!(
+        AuthError::from(JsValue::from(fetch_failed)),
+        AuthError::Network("Failed to fetch".into())
+    );
+
+    let bug = js_sys::TypeError::new("Cannot read properties of undefined (reading 'idToken')");
+    assert_eq!(
+        AuthError::from(JsValue::from(bug)),
+        AuthError::Unknown("Cannot read properties of undefined (reading 'idToken')".into())
+    );
+}
+
+#[wasm_bindgen_test(async)]
+async fn test_authorized_client_network_error() {
+    let auth = Auth::new(AuthOptions::new("not-an-api-key"));
+    let client = AuthorizedClient::new(auth).with_base_url("http://127.0.0.1:1");
+
+    assert!(matches!(
+        client.get::<()>("/not-a-server").await,
+        Err(AuthError::Network(_))
+    ));
+}
+
+#[wasm_bindgen_test(async)]
+async fn test_auth_with_memory_storage() {
+    let storage = MemoryStorage::new();
+    let auth = Auth::new(AuthOptions::new("not-an-api-key").with_storage(storage.clone()));
+
+    assert!(auth.sign_out().await.is_ok());
+    assert_eq!(
+        storage
+            .get("Auth:User:not-an-api-key:default")
+            .await
+            .unwrap(),
+        None
+    );
+}
+
+#[wasm_bindgen_test(async)]
+async fn test_auth_with_persistence_none() {
+    let auth = Auth::new(AuthOptions::new("not-an-api-key").with_persistence(Persistence::None));
+
+    assert!(auth.sign_out().await.is_ok());
+    assert_eq!(
+        auth.fetch_profile().await.err(),
+        Some(AuthError::NotSignedIn)
+    );
+}
+
+#[wasm_bindgen_test(async)]
+async fn test_auth_named_instances() {
+    let admin = Auth::new(AuthOptions::new("not-an-api-key").with_name("admin"));
+    let impersonated = Auth::new(AuthOptions::new("not-an-api-key").with_name("impersonated"));
+
+    assert_eq!(admin.name(), "admin");
+    assert_eq!(impersonated.name(), "impersonated");
+    assert_eq!(
+        Auth::instance("not-an-api-key", "admin").map(|auth| auth.name()),
+        Some("admin".into())
+    );
+    assert!(Auth::instance("another-api-key", "admin").is_none());
+    assert!(Auth::instance("not-an-api-key", "not-registered").is_none());
+}
+
+#[wasm_bindgen_test(async)]
+async fn test_auth_instances_per_project() {
+    let auth = Auth::new(AuthOptions::new("not-an-api-key").with_name("project"));
+    let other = Auth::new(AuthOptions::new("another-api-key").with_name("project"));
+    assert!(other != auth);
+
+    assert!(Auth::get_or_init(AuthOptions::new("not-an-api-key").with_name("project")) == auth);
+    assert!(Auth::instance("another-api-key", "project") == Some(other));
+
+    // `new` always creates the instance, replacing the registered one.
+    let replaced = Auth::new(AuthOptions::new("not-an-api-key").with_name("project"));
+    assert!(replaced != auth);
+    assert!(Auth::instance("not-an-api-key", "project") == Some(replaced.clone()));
+
+    assert!(Auth::remove_instance("not-an-api-key", "project") == Some(replaced));
+    assert!(Auth::instance("not-an-api-key", "project").is_none());
+    assert!(Auth::remove_instance("not-an-api-key", "project").is_none());
+}
+
+#[wasm_bindgen_test(async)]
+async fn test_auth_id_token() {
+    let auth = Auth::new(AuthOptions::new("not-an-api-key"));
+
+    assert!(auth.sign_out().await.is_ok());
+    assert_eq!(auth.current_user(), Ok(None));
+    assert_eq!(auth.id_token().await.err(), Some(AuthError::NotSignedIn));
+}
+
+#[wasm_bindgen_test(async)]
+async fn test_auth_get_id_token_force_refresh() {
+    let auth = Auth::new(AuthOptions::new("not-an-api-key"));
+
+    assert!(auth.sign_out().await.is_ok());
+    assert_eq!(
+        auth.get_id_token(true).await.err(),
+        Some(AuthError::NotSignedIn)
+    );
+}
+
+#[wasm_bindgen_test(async)]
+async fn test_auth_token_refresher() {
+    let auth = Auth::new(AuthOptions::new("not-an-api-key"));
+    assert!(auth.sign_out().await.is_ok());
+
+    // Waits for a sign-in, and stops when dropped.
+    let refresher = auth.start_token_refresher(chrono::Duration::minutes(5));
+    drop(refresher);
+    assert_eq!(auth.current_user(), Ok(None));
 }
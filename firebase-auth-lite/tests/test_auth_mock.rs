@@ -0,0 +1,146 @@
+//! The browser `Auth` against `firebase-auth-lite-mock`, which has to be running first,
+//! so these only build with the `mock-tests` feature:
+//!
+//! ```text
+//! cargo run -p firebase-auth-lite-mock
+//! cd firebase-auth-lite && wasm-pack test --headless --firefox -- --features mock-tests --test test_auth_mock
+//! ```
+//!
+//! Set `FIREBASE_AUTH_MOCK_HOST` at build time if it listens elsewhere than `127.0.0.1:9099`.
+//! The server outlives the tests, so every test signs up with a new email.
+#![cfg(feature = "mock-tests")]
+
+use firebase_auth_lite::{
+    ActionCodeSettings, Auth, AuthError, AuthOptions, AuthStorage, MemoryStorage, OobRequest,
+    ProfileUpdate,
+};
+use wasm_bindgen_test::*;
+
+wasm_bindgen_test_configure!(run_in_browser);
+
+const MOCK_HOST: &str = match option_env!("FIREBASE_AUTH_MOCK_HOST") {
+    Some(host) => host,
+    None => "127.0.0.1:9099",
+};
+
+fn mock_auth(name: &str, storage: &MemoryStorage) -> Auth {
+    Auth::new(
+        AuthOptions::new("any-api-key")
+            .with_name(name)
+            .with_emulator(MOCK_HOST)
+            .with_storage(storage.clone()),
+    )
+}
+
+fn unique_email(prefix: &str) -> String {
+    let suffix = (js_sys::Math::random() * 1e12) as u64;
+    format!("{}-{}-{}@example.com", prefix, js_sys::Date::now(), suffix)
+}
+
+#[wasm_bindgen_test(async)]
+async fn test_mock_sign_up_and_sign_in() {
+    let auth = mock_auth("mock-sign-in", &MemoryStorage::new());
+    let email = unique_email("sign-in");
+
+    let user = auth
+        .sign_up(email.clone(), "password".into())
+        .await
+        .unwrap();
+    assert_eq!(user.email.as_deref(), Some(email.as_str()));
+    assert_eq!(auth.current_user(), Ok(Some(user.clone())));
+    assert_eq!(
+        auth.sign_up(email.clone(), "password".into()).await.err(),
+        Some(AuthError::EmailExists)
+    );
+
+    auth.sign_out().await.unwrap();
+    assert_eq!(auth.current_user(), Ok(None));
+
+    assert_eq!(
+        auth.sign_in(email.clone(), "wrong-password".into())
+            .await
+            .err(),
+        Some(AuthError::InvalidPassword)
+    );
+    let signed_in = auth.sign_in(email, "password".into()).await.unwrap();
+    assert_eq!(signed_in.local_id, user.local_id);
+    assert!(auth.id_token().await.is_ok());
+}
+
+#[wasm_bindgen_test(async)]
+async fn test_mock_send_oob_code_error() {
+    let auth = mock_auth("mock-oob-error", &MemoryStorage::new());
+
+    assert_eq!(
+        auth.send_oob_code(OobRequest::PasswordReset, unique_email("unknown"))
+            .await
+            .err(),
+        Some(AuthError::EmailNotFound)
+    );
+    // Verifying an email needs a signed-in user.
+    assert_eq!(
+        auth.send_oob_code(OobRequest::VerifyEmail, "".into())
+            .await
+            .err(),
+        Some(AuthError::NotSignedIn)
+    );
+}
+
+#[wasm_bindgen_test(async)]
+async fn test_mock_send_sign_in_link_error() {
+    let storage = MemoryStorage::new();
+    let auth = mock_auth("mock-sign-in-link-error", &storage);
+    let settings =
+        ActionCodeSettings::new("https://example.com/finish").with_handle_code_in_app(true);
+
+    assert_eq!(
+        auth.send_sign_in_link("not-an-email".into(), &settings)
+            .await
+            .err(),
+        Some(AuthError::InvalidEmail)
+    );
+    assert_eq!(
+        storage
+            .get("Auth:EmailForSignIn:any-api-key:mock-sign-in-link-error")
+            .await
+            .unwrap(),
+        None
+    );
+
+    let email = unique_email("sign-in-link");
+    auth.send_sign_in_link(email.clone(), &settings)
+        .await
+        .unwrap();
+    assert_eq!(
+        storage
+            .get("Auth:EmailForSignIn:any-api-key:mock-sign-in-link-error")
+            .await
+            .unwrap(),
+        Some(email)
+    );
+}
+
+#[wasm_bindgen_test(async)]
+async fn test_mock_update_profile() {
+    let auth = mock_auth("mock-update-profile", &MemoryStorage::new());
+    auth.sign_up(unique_email("update"), "password".into())
+        .await
+        .unwrap();
+
+    let user = auth
+        .update_profile(
+            ProfileUpdate::new()
+                .with_display_name("Example User")
+                .with_photo_url("https://example.com/photo.png"),
+        )
+        .await
+        .unwrap();
+    assert_eq!(user.display_name.as_deref(), Some("Example User"));
+    assert_eq!(
+        user.photo_url.as_deref(),
+        Some("https://example.com/photo.png")
+    );
+
+    let fetched = auth.fetch_profile().await.unwrap();
+    assert_eq!(fetched.display_name.as_deref(), Some("Example User"));
+}
@@ -0,0 +1,54 @@
+use firebase_auth_lite::AuthError;
+
+#[test]
+fn test_error_from_code() {
+    assert_eq!(
+        AuthError::from_message("EMAIL_EXISTS"),
+        AuthError::EmailExists
+    );
+    assert_eq!(
+        AuthError::from_message("TOKEN_EXPIRED"),
+        AuthError::TokenExpired
+    );
+    assert_eq!(
+        AuthError::from_message("TOO_MANY_ATTEMPTS_TRY_LATER"),
+        AuthError::TooManyAttemptsTryLater
+    );
+}
+
+#[test]
+fn test_error_from_code_with_explanation() {
+    assert_eq!(
+        AuthError::from_message("WEAK_PASSWORD : Password should be at least 6 characters"),
+        AuthError::WeakPassword
+    );
+}
+
+#[test]
+fn test_error_from_main_js_message() {
+    assert_eq!(
+        AuthError::from_message("The user must be signed-in to use this method."),
+        AuthError::NotSignedIn
+    );
+    assert_eq!(
+        AuthError::from_message("API key not valid. Please pass a valid API key."),
+        AuthError::InvalidApiKey
+    );
+}
+
+#[test]
+fn test_error_unknown() {
+    let error = AuthError::from_message("SOMETHING_NEW");
+
+    assert_eq!(error, AuthError::Unknown("SOMETHING_NEW".into()));
+    assert_eq!(error.code(), None);
+    assert_eq!(error.to_string(), "SOMETHING_NEW");
+}
+
+#[test]
+fn test_error_code_round_trip() {
+    let error = AuthError::from_message("USER_DISABLED");
+
+    assert_eq!(error.code(), Some("USER_DISABLED"));
+    assert_eq!(AuthError::from_message(error.code().unwrap()), error);
+}
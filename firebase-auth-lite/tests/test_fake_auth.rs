@@ -0,0 +1,225 @@
+#![cfg(feature = "testing")]
+
+use chrono::Duration;
+use firebase_auth_lite::{
+    testing::FakeAuth, AuthClient, AuthError, AuthStreams, ProfileAttribute, ProfileUpdate,
+};
+use futures::{executor::block_on, StreamExt};
+use serde_json::json;
+
+#[test]
+fn test_fake_sign_in() {
+    let auth = FakeAuth::new();
+    let added = auth.add_user("user@example.com", "password");
+
+    let user = block_on(auth.sign_in("user@example.com".into(), "password".into())).unwrap();
+
+    assert_eq!(user.local_id, added.local_id);
+    assert_eq!(
+        auth.current_user().map(|user| user.local_id),
+        Some(added.local_id)
+    );
+}
+
+#[test]
+fn test_fake_sign_in_errors() {
+    let auth = FakeAuth::new();
+    auth.add_user("user@example.com", "password");
+
+    assert_eq!(
+        block_on(auth.sign_in("other@example.com".into(), "password".into())).err(),
+        Some(AuthError::EmailNotFound)
+    );
+    assert_eq!(
+        block_on(auth.sign_in("user@example.com".into(), "wrong".into())).err(),
+        Some(AuthError::InvalidPassword)
+    );
+
+    auth.disable_user("user@example.com");
+    assert_eq!(
+        block_on(auth.sign_in("user@example.com".into(), "password".into())).err(),
+        Some(AuthError::UserDisabled)
+    );
+}
+
+#[test]
+fn test_fake_sign_up_email_exists() {
+    let auth = FakeAuth::new();
+
+    assert!(block_on(auth.sign_up("user@example.com".into(), "password".into())).is_ok());
+    assert_eq!(
+        block_on(auth.sign_up("user@example.com".into(), "password".into())).err(),
+        Some(AuthError::EmailExists)
+    );
+}
+
+#[test]
+fn test_fake_scripted_failures() {
+    let auth = FakeAuth::new();
+    auth.add_user("user@example.com", "password");
+
+    auth.fail_next(AuthError::TooManyAttemptsTryLater);
+    auth.fail_next(AuthError::UserDisabled);
+
+    assert_eq!(
+        block_on(auth.sign_in("user@example.com".into(), "password".into())).err(),
+        Some(AuthError::TooManyAttemptsTryLater)
+    );
+    assert_eq!(
+        block_on(auth.sign_in("user@example.com".into(), "password".into())).err(),
+        Some(AuthError::UserDisabled)
+    );
+    assert!(block_on(auth.sign_in("user@example.com".into(), "password".into())).is_ok());
+}
+
+#[test]
+fn test_fake_token_expiry() {
+    let auth = FakeAuth::new();
+    auth.set_token_lifetime(Duration::minutes(5));
+    block_on(auth.sign_up("user@example.com".into(), "password".into())).unwrap();
+
+    let first = block_on(auth.id_token()).unwrap();
+    assert_eq!(block_on(auth.id_token()).unwrap(), first);
+
+    auth.advance_time(Duration::minutes(6));
+    let refreshed = block_on(auth.id_token()).unwrap();
+    assert_ne!(refreshed, first);
+
+    auth.advance_time(Duration::minutes(6));
+    auth.revoke_refresh_tokens();
+    assert_eq!(
+        block_on(auth.id_token()).err(),
+        Some(AuthError::TokenExpired)
+    );
+}
+
+#[test]
+fn test_fake_auth_state_stream() {
+    let auth = FakeAuth::new();
+    // Through the trait, like code that also runs against the browser `Auth`.
+    let mut stream = AuthStreams::auth_state_stream(&auth);
+
+    assert_eq!(block_on(stream.next()), Some(Ok(None)));
+
+    let user = block_on(auth.sign_up("user@example.com".into(), "password".into())).unwrap();
+    assert_eq!(block_on(stream.next()), Some(Ok(Some(user))));
+
+    block_on(auth.sign_out()).unwrap();
+    assert_eq!(block_on(stream.next()), Some(Ok(None)));
+}
+
+#[test]
+fn test_fake_sign_in_anonymously() {
+    let auth = FakeAuth::new();
+
+    let user = block_on(auth.sign_in_anonymously()).unwrap();
+    assert!(user.is_anonymous());
+    assert_eq!(auth.current_user(), Some(user));
+
+    let id_token = block_on(auth.id_token()).unwrap();
+    assert_eq!(id_token.claims().firebase.sign_in_provider, "anonymous");
+    assert_eq!(id_token.claims().email, None);
+}
+
+#[test]
+fn test_fake_link_with_email_password() {
+    let auth = FakeAuth::new();
+    auth.add_user("taken@example.com", "password");
+    let guest = block_on(auth.sign_in_anonymously()).unwrap();
+
+    assert_eq!(
+        block_on(auth.link_with_email_password("taken@example.com".into(), "password".into()))
+            .err(),
+        Some(AuthError::EmailExists)
+    );
+
+    let user =
+        block_on(auth.link_with_email_password("guest@example.com".into(), "password".into()))
+            .unwrap();
+    assert_eq!(user.local_id, guest.local_id);
+    assert!(!user.is_anonymous());
+    assert_ne!(user.token_manager, guest.token_manager);
+
+    block_on(auth.sign_out()).unwrap();
+    let signed_in = block_on(auth.sign_in("guest@example.com".into(), "password".into())).unwrap();
+    assert_eq!(signed_in.local_id, guest.local_id);
+}
+
+#[test]
+fn test_fake_id_token_stream() {
+    let auth = FakeAuth::new();
+    let mut stream = auth.id_token_stream();
+
+    assert_eq!(block_on(stream.next()), Some(Ok(None)));
+
+    block_on(auth.sign_up("user@example.com".into(), "password".into())).unwrap();
+    let signed_in = block_on(stream.next()).unwrap().unwrap().unwrap();
+
+    auth.advance_time(Duration::seconds(1));
+    let refreshed = block_on(auth.get_id_token(true)).unwrap();
+    assert_ne!(refreshed, signed_in);
+    assert_eq!(block_on(stream.next()), Some(Ok(Some(refreshed))));
+
+    block_on(auth.sign_out()).unwrap();
+    assert_eq!(block_on(stream.next()), Some(Ok(None)));
+}
+
+#[test]
+fn test_fake_update_profile_and_delete_account() {
+    let auth = FakeAuth::new();
+    block_on(auth.sign_up("user@example.com".into(), "password".into())).unwrap();
+
+    let user =
+        block_on(auth.update_profile(ProfileUpdate::new().with_display_name("Example User")))
+            .unwrap();
+    assert_eq!(user.display_name.as_deref(), Some("Example User"));
+    assert_eq!(auth.current_user(), Some(user));
+
+    let user = block_on(
+        auth.update_profile(ProfileUpdate::new().delete_attribute(ProfileAttribute::DisplayName)),
+    )
+    .unwrap();
+    assert_eq!(user.display_name, None);
+
+    block_on(auth.delete_account()).unwrap();
+    assert_eq!(auth.current_user(), None);
+    assert_eq!(
+        block_on(auth.sign_in("user@example.com".into(), "password".into())).err(),
+        Some(AuthError::EmailNotFound)
+    );
+}
+
+#[test]
+fn test_fake_id_token_claims() {
+    let auth = FakeAuth::new();
+    let user = block_on(auth.sign_up("user@example.com".into(), "password".into())).unwrap();
+    auth.set_custom_claims("user@example.com", &json!({ "admin": true }));
+
+    // Custom claims are only in the tokens issued after they are set.
+    let id_token = block_on(auth.id_token()).unwrap();
+    assert_eq!(id_token.claims().sub, user.local_id);
+    assert_eq!(id_token.claims().firebase.sign_in_provider, "password");
+    assert!(id_token.claims().custom.is_empty());
+
+    let user = block_on(auth.sign_in("user@example.com".into(), "password".into())).unwrap();
+    let id_token = block_on(auth.id_token()).unwrap();
+    assert_eq!(
+        Some(id_token.as_str()),
+        user.token_manager.as_ref().map(|t| t.id_token.as_str())
+    );
+    assert_eq!(id_token.claims().custom["admin"], true);
+}
+
+#[test]
+fn test_fake_force_refresh_custom_claims() {
+    let auth = FakeAuth::new();
+    block_on(auth.sign_up("user@example.com".into(), "password".into())).unwrap();
+    let first = block_on(auth.id_token()).unwrap();
+
+    auth.set_custom_claims("user@example.com", &json!({ "admin": true }));
+    assert_eq!(block_on(auth.get_id_token(false)).unwrap(), first);
+
+    auth.advance_time(Duration::seconds(1));
+    let refreshed = block_on(auth.get_id_token(true)).unwrap();
+    assert_eq!(refreshed.claims().custom["admin"], true);
+}
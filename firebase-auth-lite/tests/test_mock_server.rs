@@ -0,0 +1,590 @@
+#![cfg(feature = "native")]
+
+use firebase_auth_lite::{
+    native::Auth, ActionCodeOperation, ActionCodeSettings, AuthError, AuthOptions, EmailAction,
+    EmailActionOutcome, OobRequest, ProfileAttribute, ProfileUpdate,
+};
+use firebase_auth_lite_mock::{MockServer, PROJECT_ID};
+use serde_json::json;
+use std::time::Duration;
+
+async fn mock_auth() -> (MockServer, Auth) {
+    let server = MockServer::start().await.unwrap();
+    let auth = Auth::new(AuthOptions::new("any-api-key").with_emulator(&server.host()));
+    (server, auth)
+}
+
+#[tokio::test]
+async fn test_mock_sign_up_and_sign_in() {
+    let (_server, auth) = mock_auth().await;
+
+    let user = auth
+        .sign_up("user@example.com".into(), "password".into())
+        .await
+        .unwrap();
+    assert_eq!(user.email.as_deref(), Some("user@example.com"));
+    assert_eq!(user.provider_ids().collect::<Vec<_>>(), ["password"]);
+    assert!(user.token_manager.is_some());
+
+    auth.sign_out().await.unwrap();
+    assert_eq!(auth.current_user(), Ok(None));
+
+    let signed_in = auth
+        .sign_in("user@example.com".into(), "password".into())
+        .await
+        .unwrap();
+    assert_eq!(signed_in.local_id, user.local_id);
+    assert!(signed_in.last_login_at.is_some());
+}
+
+#[tokio::test]
+async fn test_mock_sign_in_anonymously() {
+    let (_server, auth) = mock_auth().await;
+
+    let user = auth.sign_in_anonymously().await.unwrap();
+    assert!(user.is_anonymous());
+    assert_eq!(auth.current_user(), Ok(Some(user)));
+
+    let id_token = auth.id_token().await.unwrap();
+    assert_eq!(id_token.claims().firebase.sign_in_provider, "anonymous");
+}
+
+#[tokio::test]
+async fn test_mock_link_with_email_password() {
+    let (_server, auth) = mock_auth().await;
+    auth.sign_up("taken@example.com".into(), "password".into())
+        .await
+        .unwrap();
+
+    let guest = auth.sign_in_anonymously().await.unwrap();
+    assert_eq!(
+        auth.link_with_email_password("taken@example.com".into(), "password".into())
+            .await
+            .err(),
+        Some(AuthError::EmailExists)
+    );
+
+    let user = auth
+        .link_with_email_password("guest@example.com".into(), "password".into())
+        .await
+        .unwrap();
+    assert_eq!(user.local_id, guest.local_id);
+    assert_eq!(user.email.as_deref(), Some("guest@example.com"));
+    assert!(!user.is_anonymous());
+    assert_eq!(auth.current_user(), Ok(Some(user.clone())));
+
+    auth.sign_out().await.unwrap();
+    let signed_in = auth
+        .sign_in("guest@example.com".into(), "password".into())
+        .await
+        .unwrap();
+    assert_eq!(signed_in.local_id, guest.local_id);
+}
+
+#[tokio::test]
+async fn test_mock_sign_in_errors() {
+    let (server, auth) = mock_auth().await;
+    auth.sign_up("user@example.com".into(), "password".into())
+        .await
+        .unwrap();
+
+    assert_eq!(
+        auth.sign_up("user@example.com".into(), "password".into())
+            .await
+            .err(),
+        Some(AuthError::EmailExists)
+    );
+    assert_eq!(
+        auth.sign_up("other@example.com".into(), "short".into())
+            .await
+            .err(),
+        Some(AuthError::WeakPassword)
+    );
+    assert_eq!(
+        auth.sign_in("other@example.com".into(), "password".into())
+            .await
+            .err(),
+        Some(AuthError::EmailNotFound)
+    );
+    assert_eq!(
+        auth.sign_in("user@example.com".into(), "wrong-password".into())
+            .await
+            .err(),
+        Some(AuthError::InvalidPassword)
+    );
+
+    server.disable_user("user@example.com");
+    assert_eq!(
+        auth.sign_in("user@example.com".into(), "password".into())
+            .await
+            .err(),
+        Some(AuthError::UserDisabled)
+    );
+}
+
+#[tokio::test]
+async fn test_mock_sign_in_with_custom_token() {
+    let (_server, auth) = mock_auth().await;
+
+    let user = auth
+        .sign_in_with_custom_token("some-uid".into())
+        .await
+        .unwrap();
+    assert_eq!(user.local_id, "some-uid");
+    assert!(user.custom_auth);
+
+    assert_eq!(
+        auth.sign_in_with_custom_token("".into()).await.err(),
+        Some(AuthError::InvalidCustomToken)
+    );
+}
+
+#[tokio::test]
+async fn test_mock_update_and_fetch_profile() {
+    let (_server, auth) = mock_auth().await;
+    auth.sign_up("user@example.com".into(), "password".into())
+        .await
+        .unwrap();
+
+    let updated = auth
+        .update_profile(
+            ProfileUpdate::new()
+                .with_display_name("Example User")
+                .with_photo_url("https://example.com/me.png"),
+        )
+        .await
+        .unwrap();
+    assert_eq!(updated.display_name.as_deref(), Some("Example User"));
+    assert_eq!(auth.current_user(), Ok(Some(updated)));
+
+    let user = auth.fetch_profile().await.unwrap();
+    assert_eq!(user.display_name.as_deref(), Some("Example User"));
+    assert_eq!(
+        user.photo_url.as_deref(),
+        Some("https://example.com/me.png")
+    );
+
+    let updated = auth
+        .update_profile(ProfileUpdate::new().delete_attribute(ProfileAttribute::PhotoUrl))
+        .await
+        .unwrap();
+    assert_eq!(updated.photo_url, None);
+    assert_eq!(auth.fetch_profile().await.unwrap().photo_url, None);
+}
+
+#[tokio::test]
+async fn test_mock_update_password() {
+    let (_server, auth) = mock_auth().await;
+    let user = auth
+        .sign_up("user@example.com".into(), "password".into())
+        .await
+        .unwrap();
+
+    auth.update_profile(ProfileUpdate::new().with_password("new-password"))
+        .await
+        .unwrap();
+    // Changing the password issues a new ID token.
+    assert_ne!(
+        auth.current_user().unwrap().unwrap().token_manager,
+        user.token_manager
+    );
+    assert!(auth.fetch_profile().await.is_ok());
+
+    auth.sign_out().await.unwrap();
+    assert!(auth
+        .sign_in("user@example.com".into(), "new-password".into())
+        .await
+        .is_ok());
+}
+
+#[tokio::test]
+async fn test_mock_password_reset() {
+    let (server, auth) = mock_auth().await;
+    auth.sign_up("user@example.com".into(), "password".into())
+        .await
+        .unwrap();
+
+    auth.send_oob_code(OobRequest::PasswordReset, "user@example.com".into())
+        .await
+        .unwrap();
+    let code = server.oob_codes().pop().unwrap();
+    assert_eq!(code.request_type, "PASSWORD_RESET");
+    assert_eq!(code.email, "user@example.com");
+
+    assert_eq!(
+        auth.verify_password_reset_code(code.code.clone())
+            .await
+            .unwrap(),
+        "user@example.com"
+    );
+
+    let email = auth
+        .reset_password(code.code.clone(), "new-password".into())
+        .await
+        .unwrap();
+    assert_eq!(email, "user@example.com");
+    assert!(auth
+        .sign_in("user@example.com".into(), "new-password".into())
+        .await
+        .is_ok());
+
+    // The code can only be used once.
+    assert_eq!(
+        auth.reset_password(code.code, "other-password".into())
+            .await
+            .err(),
+        Some(AuthError::InvalidOobCode)
+    );
+}
+
+#[tokio::test]
+async fn test_mock_send_verify_email() {
+    let (server, auth) = mock_auth().await;
+    auth.sign_up("user@example.com".into(), "password".into())
+        .await
+        .unwrap();
+
+    auth.send_oob_code(OobRequest::VerifyEmail, "".into())
+        .await
+        .unwrap();
+
+    let code = server.oob_codes().pop().unwrap();
+    assert_eq!(code.request_type, "VERIFY_EMAIL");
+    assert_eq!(code.email, "user@example.com");
+
+    let info = auth.check_action_code(code.code.clone()).await.unwrap();
+    assert_eq!(info.operation, ActionCodeOperation::VerifyEmail);
+    assert_eq!(info.email, "user@example.com");
+    assert_eq!(
+        auth.verify_password_reset_code(code.code.clone())
+            .await
+            .err(),
+        Some(AuthError::InvalidOobCode)
+    );
+
+    auth.apply_action_code(code.code.clone()).await.unwrap();
+    assert!(auth.fetch_profile().await.unwrap().email_verified);
+    assert_eq!(
+        auth.apply_action_code(code.code).await.err(),
+        Some(AuthError::InvalidOobCode)
+    );
+}
+
+#[tokio::test]
+async fn test_mock_send_oob_code_with_settings() {
+    let (server, auth) = mock_auth().await;
+    let settings = ActionCodeSettings::new("https://example.com/finish")
+        .with_handle_code_in_app(true)
+        .with_ios_bundle_id("com.example.ios")
+        .with_android_package_name("com.example.android", true);
+
+    auth.send_oob_code_with_settings(
+        OobRequest::EmailSignIn,
+        "user@example.com".into(),
+        &settings,
+    )
+    .await
+    .unwrap();
+
+    let code = server.oob_codes().pop().unwrap();
+    assert_eq!(code.request_type, "EMAIL_SIGNIN");
+    assert_eq!(
+        code.continue_url.as_deref(),
+        Some("https://example.com/finish")
+    );
+    assert!(code.can_handle_code_in_app);
+    assert_eq!(code.ios_bundle_id.as_deref(), Some("com.example.ios"));
+    assert_eq!(
+        code.android_package_name.as_deref(),
+        Some("com.example.android")
+    );
+}
+
+#[tokio::test]
+async fn test_mock_email_link_sign_in() {
+    let (server, auth) = mock_auth().await;
+    let settings =
+        ActionCodeSettings::new("https://example.com/finish").with_handle_code_in_app(true);
+
+    auth.send_sign_in_link("user@example.com".into(), &settings)
+        .await
+        .unwrap();
+    let link = server.oob_codes().pop().unwrap().link();
+    assert!(auth.is_sign_in_with_email_link(&link));
+
+    // The email was remembered when the link was sent.
+    let user = auth.complete_email_link_sign_in(&link, None).await.unwrap();
+    assert_eq!(user.email.as_deref(), Some("user@example.com"));
+    assert!(user.email_verified);
+    assert_eq!(auth.current_user(), Ok(Some(user)));
+
+    // The link can only be used once.
+    assert_eq!(
+        auth.complete_email_link_sign_in(&link, Some("user@example.com".into()))
+            .await
+            .err(),
+        Some(AuthError::InvalidOobCode)
+    );
+}
+
+#[tokio::test]
+async fn test_mock_send_sign_in_link_error() {
+    let (server, auth) = mock_auth().await;
+    let settings =
+        ActionCodeSettings::new("https://example.com/finish").with_handle_code_in_app(true);
+
+    assert_eq!(
+        auth.send_sign_in_link("not-an-email".into(), &settings)
+            .await
+            .err(),
+        Some(AuthError::InvalidEmail)
+    );
+    assert!(server.oob_codes().is_empty());
+
+    // Nothing was remembered, so the email has to be given.
+    let link = "https://example.com/finish?mode=signIn&oobCode=code";
+    assert_eq!(
+        auth.complete_email_link_sign_in(link, None).await.err(),
+        Some(AuthError::MissingEmail)
+    );
+}
+
+#[tokio::test]
+async fn test_mock_email_link_sign_in_on_another_device() {
+    let (server, auth) = mock_auth().await;
+    let settings =
+        ActionCodeSettings::new("https://example.com/finish").with_handle_code_in_app(true);
+    auth.send_sign_in_link("user@example.com".into(), &settings)
+        .await
+        .unwrap();
+    let link = server.oob_codes().pop().unwrap().link();
+
+    let other = Auth::new(AuthOptions::new("any-api-key").with_emulator(&server.host()));
+    assert_eq!(
+        other.complete_email_link_sign_in(&link, None).await.err(),
+        Some(AuthError::MissingEmail)
+    );
+    let user = other
+        .complete_email_link_sign_in(&link, Some("user@example.com".into()))
+        .await
+        .unwrap();
+    assert_eq!(user.email.as_deref(), Some("user@example.com"));
+}
+
+#[tokio::test]
+async fn test_mock_send_verify_and_change_email() {
+    let (server, auth) = mock_auth().await;
+    auth.sign_up("old@example.com".into(), "password".into())
+        .await
+        .unwrap();
+
+    auth.send_oob_code(OobRequest::VerifyAndChangeEmail, "new@example.com".into())
+        .await
+        .unwrap();
+
+    let code = server.oob_codes().pop().unwrap();
+    assert_eq!(code.request_type, "VERIFY_AND_CHANGE_EMAIL");
+    assert_eq!(code.email, "old@example.com");
+    assert_eq!(code.new_email.as_deref(), Some("new@example.com"));
+
+    auth.apply_action_code(code.code).await.unwrap();
+    assert!(auth
+        .sign_in("new@example.com".into(), "password".into())
+        .await
+        .is_ok());
+
+    // The previous email gets a code to undo the change.
+    let recover = server.oob_codes().pop().unwrap();
+    let info = auth.check_action_code(recover.code.clone()).await.unwrap();
+    assert_eq!(info.operation, ActionCodeOperation::RecoverEmail);
+    assert_eq!(info.email, "old@example.com");
+    assert_eq!(info.new_email.as_deref(), Some("new@example.com"));
+
+    auth.apply_action_code(recover.code).await.unwrap();
+    assert!(auth
+        .sign_in("old@example.com".into(), "password".into())
+        .await
+        .is_ok());
+}
+
+#[tokio::test]
+async fn test_mock_handle_email_action() {
+    let (server, auth) = mock_auth().await;
+    let user = auth
+        .sign_up("user@example.com".into(), "password".into())
+        .await
+        .unwrap();
+
+    auth.send_oob_code(OobRequest::VerifyEmail, "".into())
+        .await
+        .unwrap();
+    let action = EmailAction::parse(&server.oob_codes().pop().unwrap().link()).unwrap();
+    assert_eq!(
+        auth.handle_email_action(&action).await.unwrap(),
+        EmailActionOutcome::EmailVerified {
+            email: "user@example.com".into()
+        }
+    );
+    assert!(auth.fetch_profile().await.unwrap().email_verified);
+    assert_eq!(
+        auth.handle_email_action(&action).await.err(),
+        Some(AuthError::InvalidOobCode)
+    );
+
+    // Resetting the password only checks the code, the page asks for the new one.
+    auth.send_oob_code(OobRequest::PasswordReset, "user@example.com".into())
+        .await
+        .unwrap();
+    let action = EmailAction::parse(&server.oob_codes().pop().unwrap().link()).unwrap();
+    assert_eq!(
+        auth.handle_email_action(&action).await.unwrap(),
+        EmailActionOutcome::ResetPassword {
+            email: "user@example.com".into()
+        }
+    );
+    assert!(auth
+        .reset_password(action.oob_code, "new-password".into())
+        .await
+        .is_ok());
+
+    auth.send_oob_code(OobRequest::VerifyAndChangeEmail, "new@example.com".into())
+        .await
+        .unwrap();
+    let action = EmailAction::parse(&server.oob_codes().pop().unwrap().link()).unwrap();
+    assert_eq!(
+        auth.handle_email_action(&action).await.unwrap(),
+        EmailActionOutcome::EmailChanged {
+            email: "new@example.com".into()
+        }
+    );
+
+    let action = EmailAction::parse(&server.oob_codes().pop().unwrap().link()).unwrap();
+    assert_eq!(
+        auth.handle_email_action(&action).await.unwrap(),
+        EmailActionOutcome::EmailRecovered {
+            email: "user@example.com".into()
+        }
+    );
+    assert_eq!(auth.fetch_profile().await.unwrap().local_id, user.local_id);
+}
+
+#[tokio::test]
+async fn test_mock_handle_email_action_sign_in() {
+    let (server, auth) = mock_auth().await;
+    let settings =
+        ActionCodeSettings::new("https://example.com/finish").with_handle_code_in_app(true);
+    auth.send_sign_in_link("user@example.com".into(), &settings)
+        .await
+        .unwrap();
+
+    let action = EmailAction::parse(&server.oob_codes().pop().unwrap().link()).unwrap();
+    assert_eq!(
+        action.continue_url.as_deref(),
+        Some("https://example.com/finish")
+    );
+    match auth.handle_email_action(&action).await.unwrap() {
+        EmailActionOutcome::SignedIn(user) => {
+            assert_eq!(user.email.as_deref(), Some("user@example.com"))
+        }
+        outcome => panic!("unexpected outcome {:?}", outcome),
+    }
+}
+
+#[tokio::test]
+async fn test_mock_refresh_id_token() {
+    let (server, auth) = mock_auth().await;
+    server.set_token_lifetime(Duration::from_secs(1));
+    let user = auth
+        .sign_up("user@example.com".into(), "password".into())
+        .await
+        .unwrap();
+
+    tokio::time::sleep(Duration::from_millis(1100)).await;
+    let id_token = auth.id_token().await.unwrap();
+    assert_ne!(
+        Some(String::from(id_token)),
+        user.token_manager.map(|t| t.id_token)
+    );
+    assert!(auth.fetch_profile().await.is_ok());
+
+    tokio::time::sleep(Duration::from_millis(1100)).await;
+    server.revoke_refresh_tokens("user@example.com");
+    assert_eq!(
+        auth.id_token().await.err(),
+        Some(AuthError::InvalidRefreshToken)
+    );
+}
+
+#[tokio::test]
+async fn test_mock_delete_account() {
+    let (_server, auth) = mock_auth().await;
+    auth.sign_up("user@example.com".into(), "password".into())
+        .await
+        .unwrap();
+
+    auth.delete_account().await.unwrap();
+    assert_eq!(auth.current_user(), Ok(None));
+    assert_eq!(
+        auth.sign_in("user@example.com".into(), "password".into())
+            .await
+            .err(),
+        Some(AuthError::EmailNotFound)
+    );
+}
+
+#[tokio::test]
+async fn test_mock_id_token_claims() {
+    let (server, auth) = mock_auth().await;
+    let user = auth
+        .sign_up("user@example.com".into(), "password".into())
+        .await
+        .unwrap();
+    server.set_custom_claims("user@example.com", &json!({ "roles": ["admin"] }));
+    auth.sign_in("user@example.com".into(), "password".into())
+        .await
+        .unwrap();
+
+    let id_token = auth.id_token().await.unwrap();
+    let claims = id_token.claims();
+    assert_eq!(claims.sub, user.local_id);
+    assert_eq!(claims.aud, PROJECT_ID);
+    assert_eq!(claims.email.as_deref(), Some("user@example.com"));
+    assert_eq!(claims.firebase.sign_in_provider, "password");
+    assert_eq!(claims.firebase.identities["email"], ["user@example.com"]);
+    assert_eq!(claims.custom["roles"], json!(["admin"]));
+    assert!(!id_token.is_expired());
+}
+
+#[tokio::test]
+async fn test_mock_force_refresh_custom_claims() {
+    let (server, auth) = mock_auth().await;
+    auth.sign_up("user@example.com".into(), "password".into())
+        .await
+        .unwrap();
+    assert!(auth
+        .get_id_token(false)
+        .await
+        .unwrap()
+        .claims()
+        .custom
+        .is_empty());
+
+    server.set_custom_claims("user@example.com", &json!({ "admin": true }));
+    assert!(auth
+        .get_id_token(false)
+        .await
+        .unwrap()
+        .claims()
+        .custom
+        .is_empty());
+    assert_eq!(
+        auth.get_id_token(true).await.unwrap().claims().custom["admin"],
+        true
+    );
+
+    auth.sign_out().await.unwrap();
+    assert_eq!(
+        auth.get_id_token(true).await.err(),
+        Some(AuthError::NotSignedIn)
+    );
+}
@@ -0,0 +1,138 @@
+#![cfg(feature = "native")]
+
+use firebase_auth_lite::{
+    native::Auth, AuthClient, AuthError, AuthOptions, OobRequest, ProfileUpdate,
+};
+
+// Nothing listens on this port, so every request fails without leaving the machine.
+fn offline_auth() -> Auth {
+    Auth::new(AuthOptions::new("not-an-api-key").with_emulator("127.0.0.1:1"))
+}
+
+#[tokio::test]
+async fn test_native_sign_out() {
+    let auth = offline_auth();
+
+    assert!(auth.sign_out().await.is_ok());
+    assert_eq!(auth.current_user(), Ok(None));
+}
+
+#[tokio::test]
+async fn test_native_sign_in_network_error() {
+    let auth = offline_auth();
+
+    assert!(matches!(
+        auth.sign_in("example@example.com".into(), "example".into())
+            .await,
+        Err(AuthError::Network(_))
+    ));
+}
+
+#[tokio::test]
+async fn test_native_sign_up_network_error() {
+    let auth = offline_auth();
+
+    assert!(matches!(
+        auth.sign_up("example@example.com".into(), "example".into())
+            .await,
+        Err(AuthError::Network(_))
+    ));
+}
+
+#[tokio::test]
+async fn test_native_fetch_profile() {
+    let auth = offline_auth();
+
+    assert_eq!(
+        auth.fetch_profile().await.err(),
+        Some(AuthError::NotSignedIn)
+    );
+}
+
+#[tokio::test]
+async fn test_native_update_profile() {
+    let auth = offline_auth();
+
+    assert_eq!(
+        auth.update_profile(ProfileUpdate::new().with_display_name("Example"))
+            .await
+            .err(),
+        Some(AuthError::NotSignedIn)
+    );
+}
+
+#[tokio::test]
+async fn test_native_send_verify_email() {
+    let auth = offline_auth();
+
+    assert_eq!(
+        auth.send_oob_code(OobRequest::VerifyEmail, "".into())
+            .await
+            .err(),
+        Some(AuthError::NotSignedIn)
+    );
+}
+
+#[tokio::test]
+async fn test_native_delete_account() {
+    let auth = offline_auth();
+
+    assert_eq!(
+        auth.delete_account().await.err(),
+        Some(AuthError::NotSignedIn)
+    );
+}
+
+#[test]
+fn test_native_is_sign_in_with_email_link() {
+    let auth = offline_auth();
+
+    assert!(auth.is_sign_in_with_email_link(
+        "https://example.com/finish?apiKey=key&mode=signIn&oobCode=code&lang=en"
+    ));
+    // Firebase Dynamic Links wrap the action link.
+    assert!(auth.is_sign_in_with_email_link(
+        "https://example.page.link/?link=https%3A%2F%2Fexample.com%2F%3Fmode%3DsignIn%26oobCode%3Dcode"
+    ));
+
+    assert!(!auth
+        .is_sign_in_with_email_link("https://example.com/finish?mode=resetPassword&oobCode=code"));
+    assert!(!auth.is_sign_in_with_email_link("https://example.com/finish?mode=signIn"));
+    assert!(!auth.is_sign_in_with_email_link("not a link"));
+}
+
+#[tokio::test]
+async fn test_native_complete_email_link_sign_in_errors() {
+    let auth = offline_auth();
+
+    assert_eq!(
+        auth.complete_email_link_sign_in("https://example.com/", Some("user@example.com".into()))
+            .await
+            .err(),
+        Some(AuthError::InvalidOobCode)
+    );
+    // Nothing was sent from this instance, so the email is unknown.
+    assert_eq!(
+        auth.complete_email_link_sign_in("https://example.com/?mode=signIn&oobCode=code", None)
+            .await
+            .err(),
+        Some(AuthError::MissingEmail)
+    );
+}
+
+async fn assert_signed_out<C: AuthClient>(client: &C) {
+    assert_eq!(client.current_user(), Ok(None));
+    assert_eq!(client.id_token().await.err(), Some(AuthError::NotSignedIn));
+    assert_eq!(
+        client.delete_account().await.err(),
+        Some(AuthError::NotSignedIn)
+    );
+}
+
+#[tokio::test]
+async fn test_native_auth_client() {
+    let auth = offline_auth();
+
+    assert!(AuthClient::sign_out(&auth).await.is_ok());
+    assert_signed_out(&auth).await;
+}
@@ -0,0 +1,73 @@
+use firebase_auth_lite::{AuthOptions, MemoryStorage, Persistence};
+
+#[test]
+fn test_options_sync_tabs_by_persistence() {
+    assert!(AuthOptions::new("not-an-api-key").sync_tabs());
+    assert!(AuthOptions::new("not-an-api-key")
+        .with_persistence(Persistence::Local)
+        .sync_tabs());
+    assert!(!AuthOptions::new("not-an-api-key")
+        .with_persistence(Persistence::Session)
+        .sync_tabs());
+    assert!(!AuthOptions::new("not-an-api-key")
+        .with_persistence(Persistence::None)
+        .sync_tabs());
+}
+
+#[test]
+fn test_options_custom_storage_does_not_sync_tabs() {
+    assert!(!AuthOptions::new("not-an-api-key")
+        .with_storage(MemoryStorage::new())
+        .sync_tabs());
+}
+
+#[test]
+fn test_options_name() {
+    assert_eq!(AuthOptions::new("not-an-api-key").name(), "default");
+    assert_eq!(
+        AuthOptions::new("not-an-api-key").with_name("admin").name(),
+        "admin"
+    );
+}
+
+#[test]
+fn test_options_default_endpoints() {
+    let options = AuthOptions::new("not-an-api-key");
+
+    assert_eq!(
+        options.identity_toolkit_url(),
+        "https://identitytoolkit.googleapis.com"
+    );
+    assert_eq!(
+        options.secure_token_url(),
+        "https://securetoken.googleapis.com"
+    );
+}
+
+#[test]
+fn test_options_with_emulator() {
+    let options = AuthOptions::new("not-an-api-key").with_emulator("localhost:9099");
+
+    assert_eq!(
+        options.identity_toolkit_url(),
+        "http://localhost:9099/identitytoolkit.googleapis.com"
+    );
+    assert_eq!(
+        options.secure_token_url(),
+        "http://localhost:9099/securetoken.googleapis.com"
+    );
+}
+
+#[test]
+fn test_options_with_endpoints() {
+    let options = AuthOptions::new("not-an-api-key").with_endpoints(
+        "https://auth.example.com/itk/",
+        "https://auth.example.com/st",
+    );
+
+    assert_eq!(
+        options.identity_toolkit_url(),
+        "https://auth.example.com/itk"
+    );
+    assert_eq!(options.secure_token_url(), "https://auth.example.com/st");
+}
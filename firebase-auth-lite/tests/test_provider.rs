@@ -0,0 +1,111 @@
+use firebase_auth_lite::{
+    provider::{Google, OauthFlowBuilder, Oidc, Saml, Twitter},
+    AuthError, OauthFlowOptions, Prompt, Provider,
+};
+use serde_json::json;
+
+#[test]
+fn test_provider_ids() {
+    assert_eq!(Provider::Google.provider_id(), "google.com");
+    assert_eq!(Provider::GitHub.provider_id(), "github.com");
+    assert_eq!(Provider::Microsoft.provider_id(), "microsoft.com");
+    assert_eq!(Provider::Apple.provider_id(), "apple.com");
+    assert_eq!(
+        Provider::Oidc("example".into()).provider_id(),
+        "oidc.example"
+    );
+    assert_eq!(
+        Provider::Saml("saml.example".into()).provider_id(),
+        "saml.example"
+    );
+    assert_eq!(
+        Provider::oidc("oidc.example"),
+        Ok(Provider::Oidc("example".into()))
+    );
+}
+
+#[test]
+fn test_provider_empty_ids() {
+    assert_eq!(Provider::oidc(""), Err(AuthError::InvalidProviderId));
+    assert_eq!(Provider::saml(" "), Err(AuthError::InvalidProviderId));
+    assert_eq!(
+        Provider::Oidc("oidc.".into()).validate(),
+        Err(AuthError::InvalidProviderId)
+    );
+    assert_eq!(Provider::Google.validate(), Ok(()));
+    assert_eq!(Oidc::new(""), Err(AuthError::InvalidProviderId));
+    assert_eq!(Saml::new("saml."), Err(AuthError::InvalidProviderId));
+}
+
+#[test]
+fn test_oauth_flow_options_body() {
+    let options = OauthFlowOptions::new(Provider::Google)
+        .with_scope("https://www.googleapis.com/auth/calendar.readonly")
+        .with_scope("https://www.googleapis.com/auth/drive.file")
+        .with_scope("https://www.googleapis.com/auth/drive.file")
+        .with_prompt(Prompt::SelectAccount)
+        .with_login_hint("user@example.com")
+        .with_hosted_domain("example.com")
+        .with_context("settings")
+        .with_link_account(true);
+
+    assert_eq!(
+        serde_json::to_value(&options).unwrap(),
+        json!({
+            "provider": "google.com",
+            "oauthScope": "https://www.googleapis.com/auth/calendar.readonly https://www.googleapis.com/auth/drive.file",
+            "customParameter": {
+                "prompt": "select_account",
+                "login_hint": "user@example.com",
+                "hd": "example.com",
+            },
+            "context": "settings",
+            "linkAccount": true,
+        })
+    );
+}
+
+#[test]
+fn test_oauth_flow_options_from_provider() {
+    let options = OauthFlowOptions::from(Provider::Oidc("example".into()));
+
+    assert_eq!(options.provider(), &Provider::Oidc("example".into()));
+    assert!(options.scopes().is_empty());
+    assert_eq!(
+        serde_json::to_value(&options).unwrap(),
+        json!({ "provider": "oidc.example" })
+    );
+}
+
+#[test]
+fn test_oauth_flow_builder() {
+    let options: OauthFlowOptions = OauthFlowBuilder::new(Google)
+        .with_scope("https://www.googleapis.com/auth/calendar.readonly")
+        .with_prompt(Prompt::SelectAccount)
+        .with_hosted_domain("example.com")
+        .into();
+    assert_eq!(
+        options,
+        OauthFlowOptions::new(Provider::Google)
+            .with_scope("https://www.googleapis.com/auth/calendar.readonly")
+            .with_prompt(Prompt::SelectAccount)
+            .with_hosted_domain("example.com")
+    );
+
+    let options: OauthFlowOptions = OauthFlowBuilder::new(Twitter)
+        .with_prompt(Prompt::Login)
+        .into();
+    assert_eq!(
+        serde_json::to_value(&options).unwrap(),
+        json!({ "provider": "twitter.com", "customParameter": { "prompt": "login" } })
+    );
+
+    let options: OauthFlowOptions = OauthFlowBuilder::new(Saml::new("example").unwrap())
+        .with_context("admin")
+        .into();
+    assert_eq!(options.provider(), &Provider::Saml("example".into()));
+    assert_eq!(
+        serde_json::to_value(&options).unwrap(),
+        json!({ "provider": "saml.example", "context": "admin" })
+    );
+}
@@ -0,0 +1,52 @@
+use firebase_auth_lite::{
+    AuthStorage, IndexedDbStorage, LocalStorage, MemoryStorage, SessionStorage,
+};
+use futures::executor::block_on;
+use wasm_bindgen_test::*;
+
+wasm_bindgen_test_configure!(run_in_browser);
+
+async fn assert_round_trip<S: AuthStorage>(storage: S) {
+    let key = "Auth:User:not-an-api-key:test";
+
+    storage.set(key, "{\"localId\":\"1\"}").await.unwrap();
+    assert_eq!(
+        storage.get(key).await.unwrap().as_deref(),
+        Some("{\"localId\":\"1\"}")
+    );
+
+    storage.remove(key).await.unwrap();
+    assert_eq!(storage.get(key).await.unwrap(), None);
+}
+
+#[test]
+fn test_memory_storage() {
+    block_on(assert_round_trip(MemoryStorage::new()));
+}
+
+#[test]
+fn test_memory_storage_clones_share_data() {
+    let storage = MemoryStorage::new();
+    let clone = storage.clone();
+
+    block_on(storage.set("key", "value")).unwrap();
+    assert_eq!(
+        block_on(clone.get("key")).unwrap().as_deref(),
+        Some("value")
+    );
+}
+
+#[wasm_bindgen_test(async)]
+async fn test_local_storage() {
+    assert_round_trip(LocalStorage).await;
+}
+
+#[wasm_bindgen_test(async)]
+async fn test_session_storage() {
+    assert_round_trip(SessionStorage).await;
+}
+
+#[wasm_bindgen_test(async)]
+async fn test_indexed_db_storage() {
+    assert_round_trip(IndexedDbStorage::new("firebase-auth-lite-test")).await;
+}
@@ -0,0 +1,56 @@
+use chrono::{TimeZone, Utc};
+use firebase_auth_lite::{AuthError, IdToken};
+use serde::Deserialize;
+
+// An unsigned token with the claims of a Google sign-in and a custom `roles` claim.
+const ID_TOKEN: &str = "eyJhbGciOiJSUzI1NiIsImtpZCI6ImtleS1pZCIsInR5cCI6IkpXVCJ9.\
+    eyJuYW1lIjoiRXhhbXBsZSBVc2VyIiwicm9sZXMiOlsiYWRtaW4iXSwiaXNzIjoiaHR0cHM6Ly9zZWN1cmV0b2tlbi5nb29nbGUuY29tL2V4YW1wbGUtcHJvamVjdCIsImF1ZCI6ImV4YW1wbGUtcHJvamVjdCIsImF1dGhfdGltZSI6MTcwMDAwMDAwMCwidXNlcl9pZCI6IlpZMXJKSzBlWUxnIiwic3ViIjoiWlkxckpLMGVZTGciLCJpYXQiOjE3MDAwMDEwMDAsImV4cCI6MTcwMDAwNDYwMCwiZW1haWwiOiJ1c2VyQGV4YW1wbGUuY29tIiwiZW1haWxfdmVyaWZpZWQiOnRydWUsImZpcmViYXNlIjp7ImlkZW50aXRpZXMiOnsiZ29vZ2xlLmNvbSI6WyIxMjM0NSJdLCJlbWFpbCI6WyJ1c2VyQGV4YW1wbGUuY29tIl19LCJzaWduX2luX3Byb3ZpZGVyIjoiZ29vZ2xlLmNvbSIsInRlbmFudCI6InRlbmFudC0xIn19.\
+    c2lnbmF0dXJl";
+
+#[test]
+fn test_id_token_standard_claims() {
+    let token = IdToken::decode(ID_TOKEN).unwrap();
+    let claims = token.claims();
+
+    assert_eq!(claims.sub, "ZY1rJK0eYLg");
+    assert_eq!(claims.aud, "example-project");
+    assert_eq!(claims.iss, "https://securetoken.google.com/example-project");
+    assert_eq!(claims.auth_time, Utc.timestamp_opt(1700000000, 0).unwrap());
+    assert_eq!(claims.iat, Utc.timestamp_opt(1700001000, 0).unwrap());
+    assert_eq!(claims.exp, Utc.timestamp_opt(1700004600, 0).unwrap());
+    assert_eq!(claims.email.as_deref(), Some("user@example.com"));
+    assert!(claims.email_verified);
+    assert_eq!(claims.firebase.sign_in_provider, "google.com");
+    assert_eq!(claims.firebase.tenant.as_deref(), Some("tenant-1"));
+    assert_eq!(claims.firebase.identities["google.com"], ["12345"]);
+
+    assert!(token.is_expired());
+    assert_eq!(token.as_str(), ID_TOKEN);
+}
+
+#[test]
+fn test_id_token_custom_claims() {
+    #[derive(Deserialize)]
+    struct Roles {
+        roles: Vec<String>,
+    }
+
+    let token = IdToken::decode(ID_TOKEN).unwrap();
+
+    // The standard claims aren't part of the custom claims.
+    assert_eq!(token.claims().custom.keys().collect::<Vec<_>>(), ["roles"]);
+    let roles: Roles = token.claims().custom_claims().unwrap();
+    assert_eq!(roles.roles, ["admin"]);
+}
+
+#[test]
+fn test_id_token_invalid() {
+    assert_eq!(
+        IdToken::decode("not-a-jwt").err(),
+        Some(AuthError::InvalidIdToken)
+    );
+    assert_eq!(
+        IdToken::decode("header.bm90LWpzb24.signature").err(),
+        Some(AuthError::InvalidIdToken)
+    );
+}
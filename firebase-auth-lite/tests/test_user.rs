@@ -0,0 +1,158 @@
+use chrono::{TimeZone, Utc};
+use firebase_auth_lite::{ProfileAttribute, ProfileUpdate, UserProfile};
+use serde::Deserialize;
+
+#[test]
+fn test_user_profile_email_password() {
+    let user: UserProfile = serde_json::from_str(
+        r#"{
+            "localId": "ZY1rJK0eYLg",
+            "email": "user@example.com",
+            "passwordHash": "UkVEQUNURUQ=",
+            "emailVerified": false,
+            "passwordUpdatedAt": 1484124177000,
+            "providerUserInfo": [
+                {
+                    "providerId": "password",
+                    "federatedId": "user@example.com",
+                    "email": "user@example.com",
+                    "rawId": "user@example.com"
+                }
+            ],
+            "validSince": "1484124177",
+            "lastLoginAt": "1484124177000",
+            "createdAt": "1484124142000",
+            "lastRefreshAt": "2017-01-11T08:42:57.000Z",
+            "tokenManager": {
+                "idToken": "id-token",
+                "refreshToken": "refresh-token",
+                "expiresAt": 1484127777000
+            }
+        }"#,
+    )
+    .unwrap();
+
+    assert_eq!(user.email.as_deref(), Some("user@example.com"));
+    assert_eq!(
+        user.password_updated_at,
+        Utc.timestamp_millis_opt(1484124177000).single()
+    );
+    assert_eq!(user.valid_since, Utc.timestamp_opt(1484124177, 0).single());
+    assert_eq!(
+        user.created_at,
+        Utc.timestamp_millis_opt(1484124142000).single()
+    );
+    assert_eq!(
+        user.last_refresh_at,
+        Utc.timestamp_millis_opt(1484124177000).single()
+    );
+    assert_eq!(user.provider_ids().collect::<Vec<_>>(), vec!["password"]);
+    assert!(!user.is_anonymous());
+
+    let token_manager = user.token_manager.unwrap();
+    assert_eq!(token_manager.id_token, "id-token");
+    assert_eq!(
+        token_manager.expires_at,
+        Utc.timestamp_millis_opt(1484127777000).single()
+    );
+}
+
+#[test]
+fn test_user_profile_anonymous() {
+    let user: UserProfile = serde_json::from_str(
+        r#"{
+            "localId": "anonymous-id",
+            "lastLoginAt": "1484124177000",
+            "createdAt": "1484124177000",
+            "lastRefreshAt": "2017-01-11T08:42:57.000Z"
+        }"#,
+    )
+    .unwrap();
+
+    assert_eq!(user.email, None);
+    assert_eq!(user.password_hash, None);
+    assert!(user.provider_user_info.is_empty());
+    assert_eq!(user.token_manager, None);
+    assert!(user.is_anonymous());
+}
+
+#[test]
+fn test_user_profile_custom_attributes() {
+    #[derive(Debug, Deserialize, PartialEq)]
+    struct Claims {
+        admin: bool,
+    }
+
+    let user: UserProfile = serde_json::from_str(
+        r#"{
+            "localId": "custom-id",
+            "customAuth": true,
+            "customAttributes": "{\"admin\": true}",
+            "tenantId": "tenant-1",
+            "providerUserInfo": [
+                {
+                    "providerId": "google.com",
+                    "rawId": "1234",
+                    "displayName": "Example User",
+                    "photoUrl": "https://example.com/photo.png"
+                }
+            ],
+            "mfaInfo": [
+                {
+                    "mfaEnrollmentId": "enrollment-id",
+                    "phoneInfo": "+15555550100",
+                    "enrolledAt": "2021-03-01T10:00:00Z"
+                }
+            ]
+        }"#,
+    )
+    .unwrap();
+
+    assert!(user.custom_auth);
+    assert_eq!(
+        user.custom_attributes::<Claims>().unwrap(),
+        Some(Claims { admin: true })
+    );
+    assert_eq!(user.tenant_id.as_deref(), Some("tenant-1"));
+    assert_eq!(user.mfa_info[0].phone_info.as_deref(), Some("+15555550100"));
+}
+
+#[test]
+fn test_user_profile_round_trip() {
+    let json = r#"{
+        "localId": "ZY1rJK0eYLg",
+        "email": "user@example.com",
+        "passwordUpdatedAt": 1484124177000,
+        "validSince": "1484124177",
+        "createdAt": "1484124142000"
+    }"#;
+    let user: UserProfile = serde_json::from_str(json).unwrap();
+    let round_trip: UserProfile =
+        serde_json::from_str(&serde_json::to_string(&user).unwrap()).unwrap();
+
+    assert_eq!(user, round_trip);
+}
+
+#[test]
+fn test_profile_update_body() {
+    let update = ProfileUpdate::new()
+        .with_display_name("Example User")
+        .with_email("user@example.com")
+        .with_password("password")
+        .delete_attribute(ProfileAttribute::PhotoUrl)
+        .delete_attribute(ProfileAttribute::PhotoUrl);
+
+    assert_eq!(
+        serde_json::to_value(&update).unwrap(),
+        serde_json::json!({
+            "displayName": "Example User",
+            "email": "user@example.com",
+            "password": "password",
+            "deleteAttribute": ["PHOTO_URL"],
+        })
+    );
+    assert_eq!(
+        serde_json::to_value(ProfileUpdate::new()).unwrap(),
+        serde_json::json!({})
+    );
+}